@@ -3,14 +3,39 @@ use std::{
     sync::atomic::{AtomicPtr, Ordering},
 };
 
+pub mod asn1;
 pub mod auto;
+pub mod base32;
+pub mod base58;
 pub mod base64;
+pub mod base85;
+pub mod charset;
+pub mod classical;
+pub mod compression;
 pub mod error;
+pub mod escape;
 pub mod hex;
+pub mod hexdump;
+pub mod html;
+pub mod jwt;
+pub mod morse;
+pub mod pem;
+pub mod protobuf;
+pub mod punycode;
+pub mod quoted_printable;
+pub mod radix;
 pub mod rot;
+pub mod score;
+pub mod serialization;
+pub mod stream;
 pub mod url;
+pub mod uuencode;
+pub mod xor;
 
 pub use error::{CodecError, Result};
+pub use stream::Stream;
+
+use stream::BufferedReader;
 
 pub type Plugin = Box<dyn Codec + 'static>;
 
@@ -31,6 +56,13 @@ pub trait Codec {
     /// Build another version of the codec with args
     fn build(&self, args: &str) -> Option<Plugin>;
 
+    /// Tells if data looks like something this codec produced, used by auto codecs to skip
+    /// candidates that would decode successfully by accident
+    fn detect(&self, data: &[u8]) -> bool {
+        let _ = data;
+        true
+    }
+
     /// Approximation decoded output size
     fn decoded_size_hint(&self, size: usize) -> usize {
         size
@@ -54,6 +86,18 @@ pub trait Codec {
         self.decode_into(data, &mut decoded)?;
         Ok(decoded)
     }
+
+    /// Wraps `reader` into a stream of encoded data. The default implementation needs the whole
+    /// input at once, codecs able to work on chunks override it.
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(BufferedReader::new(reader, move |data| self.encode(data)))
+    }
+
+    /// Wraps `reader` into a stream of decoded data. The default implementation needs the whole
+    /// input at once, codecs able to work on chunks override it.
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(BufferedReader::new(reader, move |data| self.decode(data)))
+    }
 }
 
 pub fn get_available_plugins() -> &'static [Plugin] {
@@ -64,11 +108,65 @@ pub fn get_available_plugins() -> &'static [Plugin] {
     if available_plugins_ptr.is_null() {
         let plugins = Box::new(vec![
             Box::<hex::HexCodec>::default() as Plugin,
+            Box::<hexdump::HexdumpCodec>::default() as Plugin,
+            Box::<radix::RadixCodec>::default() as Plugin,
+            Box::new(base32::Base32Codec::new(base32::Variant::Standard)) as Plugin,
+            Box::new(base32::Base32Codec::new(base32::Variant::Hex)) as Plugin,
+            Box::new(base32::Base32Codec::new(base32::Variant::Crockford)) as Plugin,
+            Box::<base58::Base58CheckCodec>::default() as Plugin,
+            Box::<base85::Ascii85Codec>::default() as Plugin,
+            Box::<base85::BtoaCodec>::default() as Plugin,
             Box::<base64::Base64StandardCodec>::default() as Plugin,
             Box::<base64::Base64UrlCodec>::default() as Plugin,
             Box::<base64::Base64AutoCodec>::default() as Plugin,
+            Box::<base58::Base58Codec>::default() as Plugin,
+            Box::<base85::Z85Codec>::default() as Plugin,
+            Box::new(uuencode::UuencodeCodec::new(uuencode::Variant::Uu)) as Plugin,
+            Box::new(uuencode::UuencodeCodec::new(uuencode::Variant::Xx)) as Plugin,
+            Box::new(compression::CompressionCodec::new(
+                compression::Format::Gzip,
+            )) as Plugin,
+            Box::new(compression::CompressionCodec::new(
+                compression::Format::Zlib,
+            )) as Plugin,
+            Box::new(compression::CompressionCodec::new(
+                compression::Format::Deflate,
+            )) as Plugin,
+            Box::new(compression::CompressionCodec::new(
+                compression::Format::Bzip2,
+            )) as Plugin,
+            Box::new(compression::CompressionCodec::new(compression::Format::Xz)) as Plugin,
+            Box::new(compression::CompressionCodec::new(
+                compression::Format::Zstd,
+            )) as Plugin,
+            Box::<pem::PemCodec>::default() as Plugin,
+            Box::<asn1::Asn1Codec>::default() as Plugin,
+            Box::<protobuf::ProtobufCodec>::default() as Plugin,
+            Box::new(serialization::SerializationCodec::new(
+                serialization::Format::MessagePack,
+            )) as Plugin,
+            Box::new(serialization::SerializationCodec::new(
+                serialization::Format::Cbor,
+            )) as Plugin,
+            Box::new(serialization::SerializationCodec::new(
+                serialization::Format::Bson,
+            )) as Plugin,
+            Box::<jwt::JwtCodec>::default() as Plugin,
+            Box::<html::HtmlCodec>::default() as Plugin,
+            Box::<quoted_printable::QuotedPrintableCodec>::default() as Plugin,
+            Box::<escape::EscapeCodec>::default() as Plugin,
             Box::<url::UrlCodec>::default() as Plugin,
+            Box::<punycode::PunycodeCodec>::default() as Plugin,
+            Box::<charset::CharsetCodec>::default() as Plugin,
             Box::<rot::RotCodec>::default() as Plugin,
+            Box::<rot::Rot47Codec>::default() as Plugin,
+            Box::<morse::MorseCodec>::default() as Plugin,
+            Box::<classical::VigenereCodec>::default() as Plugin,
+            Box::<classical::AtbashCodec>::default() as Plugin,
+            Box::<classical::AffineCodec>::default() as Plugin,
+            Box::<classical::RailFenceCodec>::default() as Plugin,
+            Box::<classical::BaconCodec>::default() as Plugin,
+            Box::<xor::XorCodec>::default() as Plugin,
             Box::<auto::AutoCodec>::default() as Plugin,
             Box::<auto::AutoRecurseCodec>::default() as Plugin,
         ]);
@@ -0,0 +1,504 @@
+use std::io::Write;
+
+use crate::codecs::{Codec, CodecError, Result};
+
+/// Width of the tag name column, values are printed after it
+const NAME_WIDTH: usize = 18;
+
+/// Integers longer than this are printed in hexadecimal
+const MAX_DECIMAL_LEN: usize = 8;
+
+/// Nesting limit, deeper encapsulated elements are shown as a hex dump
+const MAX_DEPTH: usize = 64;
+
+const BOOLEAN: u32 = 1;
+const INTEGER: u32 = 2;
+const BIT_STRING: u32 = 3;
+const OCTET_STRING: u32 = 4;
+const NULL: u32 = 5;
+const OBJECT: u32 = 6;
+const ENUMERATED: u32 = 10;
+const UTF8_STRING: u32 = 12;
+const NUMERIC_STRING: u32 = 18;
+const PRINTABLE_STRING: u32 = 19;
+const T61_STRING: u32 = 20;
+const IA5_STRING: u32 = 22;
+const UTC_TIME: u32 = 23;
+const GENERALIZED_TIME: u32 = 24;
+const VISIBLE_STRING: u32 = 26;
+const UNIVERSAL_STRING: u32 = 28;
+const BMP_STRING: u32 = 30;
+
+/// Names of common OIDs in certificates, requests and keys, as printed by OpenSSL
+const OIDS: [(&str, &str); 62] = [
+    ("1.2.840.113549.1.1.1", "rsaEncryption"),
+    ("1.2.840.113549.1.1.5", "sha1WithRSAEncryption"),
+    ("1.2.840.113549.1.1.10", "rsassaPss"),
+    ("1.2.840.113549.1.1.11", "sha256WithRSAEncryption"),
+    ("1.2.840.113549.1.1.12", "sha384WithRSAEncryption"),
+    ("1.2.840.113549.1.1.13", "sha512WithRSAEncryption"),
+    ("1.2.840.113549.1.5.12", "PBKDF2"),
+    ("1.2.840.113549.1.5.13", "PBES2"),
+    ("1.2.840.113549.1.7.1", "pkcs7-data"),
+    ("1.2.840.113549.1.7.2", "pkcs7-signedData"),
+    ("1.2.840.113549.1.7.6", "pkcs7-encryptedData"),
+    ("1.2.840.113549.1.9.1", "emailAddress"),
+    ("1.2.840.113549.1.9.3", "contentType"),
+    ("1.2.840.113549.1.9.4", "messageDigest"),
+    ("1.2.840.113549.1.9.5", "signingTime"),
+    ("1.2.840.113549.1.9.14", "Extension Request"),
+    ("1.2.840.113549.2.9", "hmacWithSHA256"),
+    ("1.2.840.10045.2.1", "id-ecPublicKey"),
+    ("1.2.840.10045.3.1.7", "prime256v1"),
+    ("1.2.840.10045.4.3.2", "ecdsa-with-SHA256"),
+    ("1.2.840.10045.4.3.3", "ecdsa-with-SHA384"),
+    ("1.2.840.10045.4.3.4", "ecdsa-with-SHA512"),
+    ("1.3.132.0.34", "secp384r1"),
+    ("1.3.132.0.35", "secp521r1"),
+    ("1.3.101.110", "X25519"),
+    ("1.3.101.112", "ED25519"),
+    ("1.3.14.3.2.26", "sha1"),
+    ("2.16.840.1.101.3.4.1.2", "aes-128-cbc"),
+    ("2.16.840.1.101.3.4.1.42", "aes-256-cbc"),
+    ("2.16.840.1.101.3.4.2.1", "sha256"),
+    ("2.16.840.1.101.3.4.2.2", "sha384"),
+    ("2.16.840.1.101.3.4.2.3", "sha512"),
+    ("2.5.4.3", "commonName"),
+    ("2.5.4.5", "serialNumber"),
+    ("2.5.4.6", "countryName"),
+    ("2.5.4.7", "localityName"),
+    ("2.5.4.8", "stateOrProvinceName"),
+    ("2.5.4.9", "streetAddress"),
+    ("2.5.4.10", "organizationName"),
+    ("2.5.4.11", "organizationalUnitName"),
+    ("0.9.2342.19200300.100.1.25", "domainComponent"),
+    ("2.5.29.14", "X509v3 Subject Key Identifier"),
+    ("2.5.29.15", "X509v3 Key Usage"),
+    ("2.5.29.17", "X509v3 Subject Alternative Name"),
+    ("2.5.29.18", "X509v3 Issuer Alternative Name"),
+    ("2.5.29.19", "X509v3 Basic Constraints"),
+    ("2.5.29.30", "X509v3 Name Constraints"),
+    ("2.5.29.31", "X509v3 CRL Distribution Points"),
+    ("2.5.29.32", "X509v3 Certificate Policies"),
+    ("2.5.29.35", "X509v3 Authority Key Identifier"),
+    ("2.5.29.37", "X509v3 Extended Key Usage"),
+    ("2.23.140.1.2.1", "domain-validated"),
+    ("2.23.140.1.2.2", "organization-validated"),
+    ("1.3.6.1.4.1.11129.2.4.2", "CT Precertificate SCTs"),
+    ("1.3.6.1.5.5.7.1.1", "Authority Information Access"),
+    ("1.3.6.1.5.5.7.2.1", "Policy Qualifier CPS"),
+    ("1.3.6.1.5.5.7.3.1", "TLS Web Server Authentication"),
+    ("1.3.6.1.5.5.7.3.2", "TLS Web Client Authentication"),
+    ("1.3.6.1.5.5.7.3.3", "Code Signing"),
+    ("1.3.6.1.5.5.7.3.4", "E-mail Protection"),
+    ("1.3.6.1.5.5.7.48.1", "OCSP"),
+    ("1.3.6.1.5.5.7.48.2", "CA Issuers"),
+];
+
+/// Identifier and length octets of an element
+struct Header {
+    class: u8,
+    constructed: bool,
+    tag: u32,
+    header_len: usize,
+    len: usize,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Self> {
+        let (&first, mut rest) = data.split_first().ok_or(CodecError::Truncated)?;
+        let mut header_len = 1;
+        let mut tag = u32::from(first & 0x1f);
+        // High tag numbers are written in base 128 in the following bytes
+        if tag == 0x1f {
+            tag = 0;
+            loop {
+                let (&b, next) = rest.split_first().ok_or(CodecError::Truncated)?;
+                rest = next;
+                header_len += 1;
+                if tag >> 25 != 0 {
+                    return Err(CodecError::Malformed("Tag number overflow"));
+                }
+                tag = (tag << 7) | u32::from(b & 0x7f);
+                if b & 0x80 == 0 {
+                    break;
+                }
+            }
+        }
+
+        let (&b, rest) = rest.split_first().ok_or(CodecError::Truncated)?;
+        header_len += 1;
+        let len = match b {
+            0..=0x7f => usize::from(b),
+            0x80 => return Err(CodecError::Malformed("Indefinite length is not DER")),
+            _ => {
+                let n = usize::from(b & 0x7f);
+                if n > size_of::<usize>() {
+                    return Err(CodecError::Malformed("Length overflow"));
+                }
+                let bytes = rest.get(..n).ok_or(CodecError::Truncated)?;
+                header_len += n;
+                bytes.iter().fold(0, |acc, b| (acc << 8) | usize::from(*b))
+            }
+        };
+        if data.len() - header_len < len {
+            return Err(CodecError::Truncated);
+        }
+
+        Ok(Self {
+            class: first >> 6,
+            constructed: first & 0x20 != 0,
+            tag,
+            header_len,
+            len,
+        })
+    }
+
+    fn is_universal(&self, tag: u32) -> bool {
+        self.class == 0 && self.tag == tag
+    }
+
+    fn name(&self) -> String {
+        let universal = match self.tag {
+            _ if self.class != 0 => None,
+            BOOLEAN => Some("BOOLEAN"),
+            INTEGER => Some("INTEGER"),
+            BIT_STRING => Some("BIT STRING"),
+            OCTET_STRING => Some("OCTET STRING"),
+            NULL => Some("NULL"),
+            OBJECT => Some("OBJECT"),
+            ENUMERATED => Some("ENUMERATED"),
+            UTF8_STRING => Some("UTF8STRING"),
+            16 => Some("SEQUENCE"),
+            17 => Some("SET"),
+            NUMERIC_STRING => Some("NUMERICSTRING"),
+            PRINTABLE_STRING => Some("PRINTABLESTRING"),
+            T61_STRING => Some("T61STRING"),
+            IA5_STRING => Some("IA5STRING"),
+            UTC_TIME => Some("UTCTIME"),
+            GENERALIZED_TIME => Some("GENERALIZEDTIME"),
+            VISIBLE_STRING => Some("VISIBLESTRING"),
+            UNIVERSAL_STRING => Some("UNIVERSALSTRING"),
+            BMP_STRING => Some("BMPSTRING"),
+            _ => None,
+        };
+        match (universal, self.class) {
+            (Some(name), _) => name.to_owned(),
+            (None, 0) => format!("univ [ {} ]", self.tag),
+            (None, 1) => format!("appl [ {} ]", self.tag),
+            (None, 2) => format!("cont [ {} ]", self.tag),
+            (None, _) => format!("priv [ {} ]", self.tag),
+        }
+    }
+}
+
+fn hex_dump(content: &[u8]) -> String {
+    let hex: String = content.iter().map(|b| format!("{b:02X}")).collect();
+    format!("[HEX DUMP]:{hex}")
+}
+
+fn format_integer(content: &[u8]) -> String {
+    if content.is_empty() || content.len() > MAX_DECIMAL_LEN {
+        return content.iter().map(|b| format!("{b:02X}")).collect();
+    }
+    // Two's complement, sign extended from the first byte
+    let init = if content[0] & 0x80 != 0 { -1 } else { 0 };
+    let value = content
+        .iter()
+        .fold(init, |acc: i64, b| (acc << 8) | i64::from(*b));
+    value.to_string()
+}
+
+fn format_oid(content: &[u8]) -> Result<String> {
+    let mut arcs = Vec::new();
+    let mut value = 0u64;
+    for (i, b) in content.iter().enumerate() {
+        if value >> 57 != 0 {
+            return Err(CodecError::Malformed("OID arc overflow"));
+        }
+        value = (value << 7) | u64::from(b & 0x7f);
+        if b & 0x80 != 0 {
+            if i == content.len() - 1 {
+                return Err(CodecError::Truncated);
+            }
+            continue;
+        }
+        // The first byte holds the two first arcs
+        if arcs.is_empty() {
+            let first = (value / 40).min(2);
+            arcs.push(first);
+            arcs.push(value - first * 40);
+        } else {
+            arcs.push(value);
+        }
+        value = 0;
+    }
+
+    let oid = arcs
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".");
+    Ok(match OIDS.iter().find(|(known, _)| *known == oid) {
+        Some((_, name)) => format!("{name} ({oid})"),
+        None => oid,
+    })
+}
+
+/// Renders `YYMMDDHHMMSSZ` and `YYYYMMDDHHMMSSZ` times as a readable date
+fn format_time(content: &[u8], tag: u32) -> String {
+    let raw = String::from_utf8_lossy(content);
+    let digits: Option<Vec<u32>> = raw
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .map(|c| c.to_digit(10))
+        .collect();
+    let Some(digits) = digits else {
+        return raw.into_owned();
+    };
+    let number = |range: std::ops::Range<usize>| -> Option<u32> {
+        digits
+            .get(range)
+            .map(|d| d.iter().fold(0, |acc, d| acc * 10 + d))
+    };
+
+    let (year, rest) = if tag == UTC_TIME {
+        // Two digits years from 1950 to 2049
+        let year = number(0..2).map(|y| if y < 50 { 2000 + y } else { 1900 + y });
+        (year, 2)
+    } else {
+        (number(0..4), 4)
+    };
+    let date = (|| {
+        Some(format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            year?,
+            number(rest..rest + 2)?,
+            number(rest + 2..rest + 4)?,
+            number(rest + 4..rest + 6)?,
+            number(rest + 6..rest + 8)?,
+            number(rest + 8..rest + 10).unwrap_or(0),
+        ))
+    })();
+    match date {
+        Some(date) if raw.ends_with('Z') => format!("{raw} ({date} UTC)"),
+        Some(date) => format!("{raw} ({date})"),
+        None => raw.into_owned(),
+    }
+}
+
+/// Value printed after the tag name of a primitive element
+fn format_value(header: &Header, content: &[u8]) -> Result<Option<String>> {
+    let text = match header.tag {
+        _ if header.class != 0 => None,
+        BOOLEAN => Some(if content == [0] { "FALSE" } else { "TRUE" }.to_owned()),
+        INTEGER | ENUMERATED => Some(format_integer(content)),
+        OBJECT => Some(format_oid(content)?),
+        NULL => return Ok(None),
+        UTF8_STRING | NUMERIC_STRING | PRINTABLE_STRING | IA5_STRING | VISIBLE_STRING => {
+            std::str::from_utf8(content).ok().map(str::to_owned)
+        }
+        T61_STRING => Some(content.iter().map(|b| char::from(*b)).collect()),
+        BMP_STRING if content.len().is_multiple_of(2) => {
+            let units: Vec<u16> = content
+                .chunks(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+            String::from_utf16(&units).ok()
+        }
+        UNIVERSAL_STRING if content.len().is_multiple_of(4) => content
+            .chunks(4)
+            .map(|c| char::from_u32(u32::from_be_bytes([c[0], c[1], c[2], c[3]])))
+            .collect(),
+        UTC_TIME | GENERALIZED_TIME => Some(format_time(content, header.tag)),
+        _ => None,
+    };
+
+    // Context specific values are often strings, like names in a subjectAltName
+    let is_text = !content.is_empty() && content.iter().all(|b| b.is_ascii_graphic() || *b == b' ');
+    Ok(Some(match text {
+        Some(text) => text,
+        None if header.class != 0 && is_text => String::from_utf8_lossy(content).into_owned(),
+        None => hex_dump(content),
+    }))
+}
+
+/// Writes one line per element, in the format of `openssl asn1parse`
+fn dump(data: &[u8], offset: usize, depth: usize, output: &mut Vec<u8>) -> Result<()> {
+    if depth > MAX_DEPTH {
+        return Err(CodecError::Malformed("Elements are nested too deeply"));
+    }
+    let mut pos = 0;
+    while pos < data.len() {
+        let header = Header::parse(&data[pos..])?;
+        let start = pos + header.header_len;
+        let content = &data[start..start + header.len];
+        let kind = if header.constructed { "cons" } else { "prim" };
+        let line = format!(
+            "{:5}:d={depth:<2} hl={} l={:4} {kind}: {:depth$}{:<NAME_WIDTH$}",
+            offset + pos,
+            header.header_len,
+            header.len,
+            "",
+            header.name(),
+        );
+
+        if header.constructed {
+            writeln!(output, "{}", line.trim_end())?;
+            dump(content, offset + start, depth + 1, output)?;
+        } else if let Some(nested) = encapsulated(&header, content, offset + start, depth + 1) {
+            writeln!(output, "{}", line.trim_end())?;
+            output.extend_from_slice(&nested[..]);
+        } else {
+            match format_value(&header, content)? {
+                Some(value) => writeln!(output, "{line}:{value}")?,
+                None => writeln!(output, "{}", line.trim_end())?,
+            }
+        }
+        pos = start + header.len;
+    }
+    Ok(())
+}
+
+/// Dump of a single DER element nested in an OCTET STRING or a BIT STRING, like certificate
+/// extensions and public keys
+fn encapsulated(header: &Header, content: &[u8], offset: usize, depth: usize) -> Option<Vec<u8>> {
+    let (content, offset) = if header.is_universal(OCTET_STRING) {
+        (content, offset)
+    } else if header.is_universal(BIT_STRING) && content.first() == Some(&0) {
+        (&content[1..], offset + 1)
+    } else {
+        return None;
+    };
+    if depth > MAX_DEPTH {
+        return None;
+    }
+
+    // Short strings would too often look like an element by accident
+    let inner = Header::parse(content).ok()?;
+    if content.len() <= 2 || inner.header_len + inner.len != content.len() {
+        return None;
+    }
+    let mut nested = Vec::new();
+    dump(content, offset, depth, &mut nested).ok()?;
+    Some(nested)
+}
+
+/// Renders DER structures as a tree, encoding copies the input
+#[derive(Default, Clone)]
+pub struct Asn1Codec;
+
+impl Codec for Asn1Codec {
+    fn name(&self) -> &'static str {
+        "asn1"
+    }
+
+    fn description(&self) -> &'static str {
+        "ASN.1 DER structure dump like openssl asn1parse, decode only"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        output.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        if data.is_empty() {
+            return Err(CodecError::Truncated);
+        }
+        dump(data, 0, 0, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        if !args.is_empty() {
+            return None;
+        }
+        Some(Box::new(Self) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        size * 16
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        // A single SEQUENCE spanning the whole input, as certificates, keys and requests are
+        Header::parse(data).is_ok_and(|header| {
+            header.constructed
+                && header.is_universal(16)
+                && header.header_len + header.len == data.len()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dump() {
+        // SEQUENCE { INTEGER -2, OID sha256WithRSAEncryption, UTCTime, [0] { OCTET STRING {
+        // BOOLEAN } }, BMPString, [2] "a.com" }
+        let der = b"\x30\x37\x02\x01\xfe\x06\x09\x2a\x86\x48\x86\xf7\x0d\x01\x01\x0b\
+            \x17\x0d\x32\x34\x30\x31\x30\x32\x30\x33\x30\x34\x30\x35\x5a\
+            \xa0\x05\x04\x03\x01\x01\xff\
+            \x1e\x04\x00\x48\x00\x69\
+            \x82\x05a.com\
+            \x04\x04\xde\xad\xbe\xef";
+        let codec = Asn1Codec;
+        assert!(codec.detect(der));
+        let tree = String::from_utf8(codec.decode(der).unwrap()).unwrap();
+        assert_eq!(
+            tree,
+            "    0:d=0  hl=2 l=  55 cons: SEQUENCE
+    2:d=1  hl=2 l=   1 prim:  INTEGER           :-2
+    5:d=1  hl=2 l=   9 prim:  OBJECT            :sha256WithRSAEncryption (1.2.840.113549.1.1.11)
+   16:d=1  hl=2 l=  13 prim:  UTCTIME           :240102030405Z (2024-01-02 03:04:05 UTC)
+   31:d=1  hl=2 l=   5 cons:  cont [ 0 ]
+   33:d=2  hl=2 l=   3 prim:   OCTET STRING
+   35:d=3  hl=2 l=   1 prim:    BOOLEAN           :TRUE
+   38:d=1  hl=2 l=   4 prim:  BMPSTRING         :Hi
+   44:d=1  hl=2 l=   5 prim:  cont [ 2 ]        :a.com
+   51:d=1  hl=2 l=   4 prim:  OCTET STRING      :[HEX DUMP]:DEADBEEF
+"
+        );
+
+        assert!(matches!(
+            codec.decode(&der[..20]),
+            Err(CodecError::Truncated)
+        ));
+        assert!(!codec.detect(b"hello"));
+    }
+
+    #[test]
+    fn oid() {
+        assert_eq!(format_oid(b"\x55\x04\x03").unwrap(), "commonName (2.5.4.3)");
+        assert_eq!(format_oid(b"\x88\x37\x01").unwrap(), "2.999.1");
+        assert!(format_oid(b"\x2a\x86").is_err());
+    }
+
+    #[test]
+    fn nesting_limit() {
+        // SEQUENCEs nested far deeper than the stack could recurse
+        let levels = 100_000;
+        let mut der = Vec::with_capacity(levels * 6);
+        for i in 0..levels {
+            let len = (levels - i - 1) * 6;
+            der.extend_from_slice(b"\x30\x84");
+            der.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+        let codec = Asn1Codec;
+        assert!(codec.detect(&der));
+        assert!(matches!(codec.decode(&der), Err(CodecError::Malformed(_))));
+
+        // OCTET STRINGs wrapping each other stop being expanded
+        let mut der = b"\x05\x00".to_vec();
+        for _ in 0..levels / 100 {
+            let mut outer = vec![0x04, 0x84];
+            outer.extend_from_slice(&(der.len() as u32).to_be_bytes());
+            outer.append(&mut der);
+            der = outer;
+        }
+        let tree = String::from_utf8(codec.decode(&der).unwrap()).unwrap();
+        assert!(tree.contains("[HEX DUMP]:"));
+    }
+}
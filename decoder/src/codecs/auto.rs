@@ -1,7 +1,133 @@
-use crate::codecs::{get_available_plugins, Codec, CodecError, Result};
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use serde_json::json;
+
+use crate::codecs::{
+    base64::Base64StandardCodec, get_available_plugins, score::plausibility, Codec, CodecError,
+    Result,
+};
+
+/// Number of bytes shown for each candidate in verbose mode
+const PREVIEW_LEN: usize = 48;
+
+/// Most layers decoded by auto-recurse
+const MAX_DEPTH: usize = 32;
+
+/// Fewest letters for a rotation to be told apart from plain text
+const MIN_ROTATION_LETTERS: usize = 8;
+
+/// Score a rotation must gain over leaving the input alone
+const ROTATION_MARGIN: f64 = 0.25;
+
+/// Successful decoding of the input by a codec
+pub struct Candidate {
+    /// Codec name and arguments, as given on the command line
+    pub name: String,
+    pub score: f64,
+    pub output: Vec<u8>,
+}
+
+fn try_decode(name: String, codec: &dyn Codec, data: &[u8]) -> Option<Candidate> {
+    if !codec.detect(data) {
+        return None;
+    }
+
+    let mut output = Vec::with_capacity(codec.decoded_size_hint(data.len()));
+    if codec.decode_into(data, &mut output).is_err() || output == data {
+        return None;
+    }
+    Some(Candidate {
+        score: plausibility(data, &output[..]),
+        name,
+        output,
+    })
+}
+
+/// Rotations of short texts often read as well as the texts themselves, so they are only kept
+/// when they clearly beat the identity
+fn is_clear_rotation(data: &[u8], candidate: &Candidate) -> bool {
+    data.iter().filter(|b| b.is_ascii_alphabetic()).count() >= MIN_ROTATION_LETTERS
+        && candidate.score >= plausibility(data, data) + ROTATION_MARGIN
+}
+
+fn fingerprint(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn parse_verbose(args: &str) -> Option<bool> {
+    match args {
+        "" => Some(false),
+        "verbose" => Some(true),
+        _ => None,
+    }
+}
 
 #[derive(Default, Clone)]
-pub struct AutoCodec;
+pub struct AutoCodec {
+    /// Show all candidates and their scores
+    verbose: bool,
+}
+
+impl AutoCodec {
+    /// Decodes `data` with all codecs, sorted from the most to the least plausible. Decodings
+    /// leaving the input unchanged are ignored.
+    pub fn candidates(&self, data: &[u8]) -> Vec<Candidate> {
+        let mut candidates = Vec::new();
+
+        for t in get_available_plugins() {
+            if t.name().starts_with("auto") {
+                continue;
+            }
+
+            let variants: Vec<String> = match t.name() {
+                // Rotations are tried with every key, leaving digits alone
+                "rot" => (1..26).map(|key| format!("{key},digits=0")).collect(),
+                // Only domain names can be told apart from regular text
+                "punycode" => vec!["idna".to_owned()],
+                _ => {
+                    candidates.extend(try_decode(t.name().to_owned(), &**t, data));
+                    continue;
+                }
+            };
+            for args in variants {
+                let codec = t.build(&args).unwrap();
+                let name = format!("{}:{args}", t.name());
+                candidates.extend(try_decode(name, &*codec, data));
+            }
+        }
+
+        candidates.retain(|c| !c.name.starts_with("rot") || is_clear_rotation(data, c));
+
+        // Stable sort, so ties are broken with the registry order
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        candidates
+    }
+
+    fn best_candidate(&self, data: &[u8]) -> Result<Candidate> {
+        let mut candidates = self.candidates(data);
+        if self.verbose {
+            for c in &candidates {
+                let preview = &c.output[..c.output.len().min(PREVIEW_LEN)];
+                eprintln!(
+                    "  {}: score {:.2} \"{}\"",
+                    c.name,
+                    c.score,
+                    preview.escape_ascii()
+                );
+            }
+        }
+
+        if candidates.is_empty() {
+            return Err(CodecError::NoCodecAvailable);
+        }
+        Ok(candidates.swap_remove(0))
+    }
+}
 
 impl Codec for AutoCodec {
     fn name(&self) -> &'static str {
@@ -9,7 +135,7 @@ impl Codec for AutoCodec {
     }
 
     fn description(&self) -> &'static str {
-        "Tries all codecs until one works"
+        "Tries all codecs and keeps the most plausible output, args: verbose"
     }
 
     fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
@@ -18,33 +144,71 @@ impl Codec for AutoCodec {
     }
 
     fn build(&self, args: &str) -> Option<super::Plugin> {
-        let _ = args;
-        Some(Box::new(Self) as super::Plugin)
+        let verbose = parse_verbose(args)?;
+        Some(Box::new(Self { verbose }) as super::Plugin)
     }
 
     fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-        let mut temp = Vec::new();
+        let mut best = self.best_candidate(data)?;
+        eprintln!("Decoded input with {}", best.name);
+        output.append(&mut best.output);
+        Ok(())
+    }
+}
 
-        for t in get_available_plugins() {
-            if t.name().starts_with("auto") || t.name() == "rot" {
-                continue;
-            }
+#[derive(Default, Clone)]
+pub struct AutoRecurseCodec {
+    /// Show all candidates and their scores at each step
+    verbose: bool,
 
-            temp.clear();
-            temp.reserve(t.decoded_size_hint(data.len()));
-            if t.decode_into(data, &mut temp).is_ok() {
-                eprintln!("Decoded input with {}", t.name());
-                output.append(&mut temp);
-                return Ok(());
+    /// Output the codecs chain and the decoded data as JSON
+    json: bool,
+}
+
+impl AutoRecurseCodec {
+    /// Decodes `data` layer by layer, returns the names of the codecs applied and the output
+    pub fn peel(&self, data: &[u8]) -> Result<(Vec<String>, Vec<u8>)> {
+        self.peel_layers(data, MAX_DEPTH)
+    }
+
+    fn peel_layers(&self, data: &[u8], max_depth: usize) -> Result<(Vec<String>, Vec<u8>)> {
+        let auto_codec = AutoCodec {
+            verbose: self.verbose,
+        };
+
+        let mut chain = Vec::new();
+        let mut next_input = data.to_vec();
+        // Codecs undoing each other would loop forever, outputs are remembered by their hash
+        let mut seen = HashSet::from([fingerprint(data)]);
+        while chain.len() < max_depth {
+            let candidate = match auto_codec.best_candidate(&next_input[..]) {
+                Err(CodecError::NoCodecAvailable) => break,
+                Ok(val) => val,
+                Err(e) => return Err(e),
+            };
+            // Past the first layer, stop once decoding makes the data less plausible
+            if !chain.is_empty() && candidate.score < plausibility(&next_input[..], &next_input[..])
+            {
+                break;
+            }
+            if !seen.insert(fingerprint(&candidate.output[..])) {
+                break;
             }
+            eprintln!("Decoded input with {}", candidate.name);
+            chain.push(candidate.name);
+            next_input = candidate.output;
         }
-
-        Err(CodecError::NoCodecAvailable)
+        Ok((chain, next_input))
     }
 }
 
-#[derive(Default, Clone)]
-pub struct AutoRecurseCodec;
+/// Command line arguments applying `names` in order
+fn command_line<'a>(names: impl Iterator<Item = &'a String>) -> String {
+    names
+        .map(|name| format!("-c {name}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
 impl Codec for AutoRecurseCodec {
     fn name(&self) -> &'static str {
@@ -52,7 +216,7 @@ impl Codec for AutoRecurseCodec {
     }
 
     fn description(&self) -> &'static str {
-        "Decode input with auto plugin until it does not change or fail"
+        "Decode input with auto plugin until it does not change or fail, args: verbose, json"
     }
 
     fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
@@ -61,32 +225,112 @@ impl Codec for AutoRecurseCodec {
     }
 
     fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-        let auto_codec = AutoCodec;
-
-        let mut next_input = auto_codec.decode(data)?;
+        let (chain, mut decoded) = self.peel(data)?;
+        let decode = command_line(chain.iter());
+        let encode = format!("-m encode {}", command_line(chain.iter().rev()));
 
-        if next_input == data {
-            output.append(&mut next_input);
+        if !self.json {
+            if !chain.is_empty() {
+                eprintln!("Replay with: {decode}");
+                eprintln!("Re-encode with: {encode}");
+            }
+            output.append(&mut decoded);
             return Ok(());
         }
 
-        loop {
-            let temp = match auto_codec.decode(&next_input[..]) {
-                Err(CodecError::NoCodecAvailable) => break,
-                Ok(val) => val,
-                Err(e) => return Err(e),
-            };
-            if temp == next_input {
-                break;
+        let decoded = match String::from_utf8(decoded) {
+            Ok(text) => json!(text),
+            Err(e) => {
+                let encoded = Base64StandardCodec::default().encode(e.as_bytes())?;
+                json!({ "base64": String::from_utf8(encoded).unwrap() })
             }
-            next_input = temp;
-        }
-        output.append(&mut next_input);
+        };
+        let document = json!({
+            "chain": chain,
+            "decode": decode,
+            "encode": encode,
+            "output": decoded,
+        });
+        serde_json::to_writer_pretty(&mut *output, &document)?;
+        output.push(b'\n');
         Ok(())
     }
 
     fn build(&self, args: &str) -> Option<super::Plugin> {
-        let _ = args;
-        Some(Box::new(Self) as super::Plugin)
+        let mut codec = Self::default();
+        for arg in args.split(',').filter(|a| !a.is_empty()) {
+            match arg {
+                "verbose" => codec.verbose = true,
+                "json" => codec.json = true,
+                _ => return None,
+            }
+        }
+        Some(Box::new(codec) as super::Plugin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_most_plausible() {
+        let auto = AutoCodec::default();
+        // Also valid base64
+        assert_eq!(
+            auto.decode(b"48656c6c6f2c20776f726c6421").unwrap(),
+            b"Hello, world!"
+        );
+        assert_eq!(
+            auto.decode(b"SGVsbG8sIHdvcmxkIQ==").unwrap(),
+            b"Hello, world!"
+        );
+    }
+
+    #[test]
+    fn plain_text_is_not_rotated() {
+        let auto = AutoCodec::default();
+        for text in [
+            &b" abc"[..],
+            b"abc def",
+            b"foobar",
+            b"hello there",
+            b"import numpy as np",
+        ] {
+            assert!(auto
+                .candidates(text)
+                .iter()
+                .all(|c| !c.name.starts_with("rot")));
+        }
+        assert_eq!(
+            auto.decode(b"Uryyb, jbeyq! Guvf vf n grfg.").unwrap(),
+            b"Hello, world! This is a test."
+        );
+    }
+
+    #[test]
+    fn recurse() {
+        let auto_recurse = AutoRecurseCodec::default();
+        let (chain, decoded) = auto_recurse
+            .peel(b"NTM0NzU2NzM2MjQ3Mzg3MzQ5NDg2NDc2NjM2ZDc4NmI0OTUxM2QzZA==")
+            .unwrap();
+        assert_eq!(chain, ["base64-standard", "hex", "base64-standard"]);
+        assert_eq!(decoded, b"Hello, world!");
+        assert_eq!(
+            command_line(chain.iter().rev()),
+            "-c base64-standard -c hex -c base64-standard"
+        );
+    }
+
+    #[test]
+    fn recurse_depth_is_bounded() {
+        let (chain, decoded) = AutoRecurseCodec::default()
+            .peel_layers(
+                b"NTM0NzU2NzM2MjQ3Mzg3MzQ5NDg2NDc2NjM2ZDc4NmI0OTUxM2QzZA==",
+                2,
+            )
+            .unwrap();
+        assert_eq!(chain, ["base64-standard", "hex"]);
+        assert_eq!(decoded, b"SGVsbG8sIHdvcmxkIQ==");
     }
 }
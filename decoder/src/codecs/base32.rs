@@ -0,0 +1,309 @@
+use crate::codecs::{
+    stream::{transform_all, Transform, TransformReader},
+    Codec, CodecError, Result, Stream,
+};
+
+const STANDARD: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const HEX: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// RFC 4648 standard alphabet
+    Standard,
+
+    /// RFC 4648 "extended hex" alphabet
+    Hex,
+
+    /// Douglas Crockford's alphabet, without I, L, O and U
+    Crockford,
+}
+
+impl Variant {
+    fn symbols(&self) -> &'static [u8; 32] {
+        match self {
+            Self::Standard => STANDARD,
+            Self::Hex => HEX,
+            Self::Crockford => CROCKFORD,
+        }
+    }
+
+    /// Value of a symbol, case insensitive. Crockford's aliases for easily confused characters
+    /// are only accepted when `lenient` is set.
+    fn value(&self, b: u8, lenient: bool) -> Option<u8> {
+        let b = b.to_ascii_uppercase();
+        if *self == Self::Crockford && lenient {
+            match b {
+                b'O' => return Some(0),
+                b'I' | b'L' => return Some(1),
+                _ => {}
+            }
+        }
+        self.symbols()
+            .iter()
+            .position(|s| *s == b)
+            .map(|pos| pos as u8)
+    }
+
+    /// Crockford's flavour has no padding but allows hyphens for readability
+    fn is_separator(&self, b: u8) -> bool {
+        b.is_ascii_whitespace() || (*self == Self::Crockford && b == b'-')
+    }
+}
+
+/// Base32 encoding with a 32 symbols alphabet, each symbol carrying 5 bits
+#[derive(Clone)]
+pub struct Base32Codec {
+    variant: Variant,
+    pad: bool,
+}
+
+impl Base32Codec {
+    pub const fn new(variant: Variant) -> Self {
+        let pad = !matches!(variant, Variant::Crockford);
+        Self { variant, pad }
+    }
+}
+
+struct Base32Encoder {
+    variant: Variant,
+    pad: bool,
+    acc: u16,
+    bits: u8,
+    chars: usize,
+}
+
+impl Transform for Base32Encoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        let symbols = self.variant.symbols();
+        for b in input {
+            self.acc = (self.acc << 8) | u16::from(*b);
+            self.bits += 8;
+            while self.bits >= 5 {
+                self.bits -= 5;
+                output.push(symbols[usize::from((self.acc >> self.bits) & 0x1f)]);
+                self.chars += 1;
+            }
+            self.acc &= (1 << self.bits) - 1;
+        }
+
+        if eof {
+            if self.bits > 0 {
+                output.push(symbols[usize::from((self.acc << (5 - self.bits)) & 0x1f)]);
+                self.chars += 1;
+                self.bits = 0;
+            }
+            if self.pad {
+                while !self.chars.is_multiple_of(8) {
+                    output.push(b'=');
+                    self.chars += 1;
+                }
+            }
+        }
+
+        Ok(input.len())
+    }
+}
+
+struct Base32Decoder {
+    variant: Variant,
+    acc: u16,
+    bits: u8,
+    symbols: usize,
+    padding: usize,
+}
+
+impl Transform for Base32Decoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        for b in input {
+            if self.variant.is_separator(*b) {
+                continue;
+            }
+            if *b == b'=' && self.variant != Variant::Crockford {
+                self.padding += 1;
+                continue;
+            }
+            if self.padding > 0 {
+                return Err(CodecError::InvalidPadding);
+            }
+
+            let value = self
+                .variant
+                .value(*b, true)
+                .ok_or(CodecError::InvalidChar(*b))?;
+            self.acc = (self.acc << 5) | u16::from(value);
+            self.bits += 5;
+            self.symbols += 1;
+            if self.bits >= 8 {
+                self.bits -= 8;
+                output.push((self.acc >> self.bits) as u8);
+                self.acc &= (1 << self.bits) - 1;
+            }
+        }
+
+        if eof {
+            // Only 2, 4, 5 or 7 symbols can end a group
+            if matches!(self.symbols % 8, 1 | 3 | 6) {
+                return Err(CodecError::Truncated);
+            }
+            if self.padding > 0 && !(self.symbols + self.padding).is_multiple_of(8) {
+                return Err(CodecError::InvalidPadding);
+            }
+        }
+
+        Ok(input.len())
+    }
+}
+
+impl Base32Codec {
+    fn new_encoder(&self) -> Base32Encoder {
+        Base32Encoder {
+            variant: self.variant,
+            pad: self.pad,
+            acc: 0,
+            bits: 0,
+            chars: 0,
+        }
+    }
+
+    fn new_decoder(&self) -> Base32Decoder {
+        Base32Decoder {
+            variant: self.variant,
+            acc: 0,
+            bits: 0,
+            symbols: 0,
+            padding: 0,
+        }
+    }
+}
+
+impl Codec for Base32Codec {
+    fn name(&self) -> &'static str {
+        match self.variant {
+            Variant::Standard => "base32",
+            Variant::Hex => "base32hex",
+            Variant::Crockford => "base32-crockford",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self.variant {
+            Variant::Standard => "Base32 standard alphabet, padded unless built with nopad",
+            Variant::Hex => "Base32 extended hex alphabet, padded unless built with nopad",
+            Variant::Crockford => "Crockford's base32, unpadded unless built with pad",
+        }
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(self.new_encoder(), data, output)
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(self.new_decoder(), data, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let pad = match args {
+            "" => Self::new(self.variant).pad,
+            "pad" => true,
+            "nopad" => false,
+            _ => return None,
+        };
+        Some(Box::new(Self {
+            variant: self.variant,
+            pad,
+        }) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        (size / 8) * 5
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        size.div_ceil(5) * 8
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let data = data.trim_ascii();
+        let body = match data.iter().position(|b| *b == b'=') {
+            Some(pos) if self.variant != Variant::Crockford => {
+                if !data.len().is_multiple_of(8) || data[pos..].iter().any(|b| *b != b'=') {
+                    return false;
+                }
+                &data[..pos]
+            }
+            _ => data,
+        };
+        // Encoded data may be wrapped, but words separated by spaces are text
+        if body.iter().any(|b| matches!(b, b' ' | b'\t')) {
+            return false;
+        }
+        let body: Vec<u8> = body
+            .iter()
+            .copied()
+            .filter(|b| !self.variant.is_separator(*b))
+            .collect();
+
+        // Only accept a single case, and let strings made of hex digits to the hex codec. Digits
+        // are a sixth of the standard alphabet, their absence hints at a plain word.
+        !body.is_empty()
+            && (self.variant != Variant::Standard || body.iter().any(u8::is_ascii_digit))
+            && matches!(body.len() % 8, 0 | 2 | 4 | 5 | 7)
+            && body.iter().all(|b| self.variant.value(*b, false).is_some())
+            && (body.iter().all(|b| !b.is_ascii_lowercase())
+                || body.iter().all(|b| !b.is_ascii_uppercase()))
+            && !body.iter().all(u8::is_ascii_hexdigit)
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, self.new_encoder()))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, self.new_decoder()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc4648_vectors() {
+        let vectors: &[(&[u8], &str, &str)] = &[
+            (b"", "", ""),
+            (b"f", "MY======", "CO======"),
+            (b"fo", "MZXQ====", "CPNG===="),
+            (b"foo", "MZXW6===", "CPNMU==="),
+            (b"foob", "MZXW6YQ=", "CPNMUOG="),
+            (b"fooba", "MZXW6YTB", "CPNMUOJ1"),
+            (b"foobar", "MZXW6YTBOI======", "CPNMUOJ1E8======"),
+        ];
+        let standard = Base32Codec::new(Variant::Standard);
+        let hex = Base32Codec::new(Variant::Hex);
+        for (raw, std_enc, hex_enc) in vectors {
+            assert_eq!(standard.encode(raw).unwrap(), std_enc.as_bytes());
+            assert_eq!(standard.decode(std_enc.as_bytes()).unwrap(), *raw);
+            assert_eq!(hex.encode(raw).unwrap(), hex_enc.as_bytes());
+            assert_eq!(hex.decode(hex_enc.as_bytes()).unwrap(), *raw);
+        }
+    }
+
+    #[test]
+    fn crockford_aliases() {
+        let crockford = Base32Codec::new(Variant::Crockford);
+        assert_eq!(crockford.encode(b"foobar").unwrap(), b"CSQPYRK1E8");
+        assert_eq!(crockford.decode(b"csqp-yrkl-e8").unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn detect_leaves_hex_alone() {
+        let standard = Base32Codec::new(Variant::Standard);
+        let hex = Base32Codec::new(Variant::Hex);
+        assert!(!standard.detect(b"deadbeef"));
+        assert!(!hex.detect(b"0123456789ABCDEF"));
+        assert!(standard.detect(b"MZXW6YTBOI======\n"));
+        assert!(!standard.detect(b"MZXW6YTBoi======"));
+        assert!(!standard.detect(b"secretpayload"));
+    }
+}
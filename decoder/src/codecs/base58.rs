@@ -0,0 +1,266 @@
+use sha2::{Digest, Sha256};
+
+use crate::codecs::{Codec, CodecError, Result};
+
+const BITCOIN: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const RIPPLE: &[u8; 58] = b"rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz";
+const FLICKR: &[u8; 58] = b"123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ";
+
+const CHECKSUM_LEN: usize = 4;
+
+/// Longest input checked by `Base58CheckCodec::detect`, extended keys take 111 characters and
+/// decoding is quadratic
+const MAX_CHECK_LEN: usize = 256;
+
+fn get_alphabet(name: &str) -> Option<&'static [u8; 58]> {
+    match name {
+        "bitcoin" => Some(BITCOIN),
+        "ripple" => Some(RIPPLE),
+        "flickr" => Some(FLICKR),
+        _ => None,
+    }
+}
+
+fn encode_into(data: &[u8], output: &mut Vec<u8>, alphabet: &[u8; 58]) {
+    // Leading zeroes are kept as-is, each one being the first symbol
+    let zeroes = data.iter().take_while(|b| **b == 0).count();
+
+    // Little endian base 58 digits
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for b in &data[zeroes..] {
+        let mut carry = u32::from(*b);
+        for d in digits.iter_mut() {
+            carry += u32::from(*d) << 8;
+            *d = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    output.extend(std::iter::repeat_n(alphabet[0], zeroes));
+    output.extend(digits.iter().rev().map(|d| alphabet[usize::from(*d)]));
+}
+
+fn decode_into(data: &[u8], output: &mut Vec<u8>, alphabet: &[u8; 58]) -> Result<()> {
+    let data = data.trim_ascii();
+    let zeroes = data.iter().take_while(|b| **b == alphabet[0]).count();
+
+    // Little endian base 256 digits
+    let mut bytes: Vec<u8> = Vec::with_capacity(data.len() * 733 / 1000 + 1);
+    for c in &data[zeroes..] {
+        let mut carry = alphabet
+            .iter()
+            .position(|s| s == c)
+            .ok_or(CodecError::InvalidChar(*c))? as u32;
+        for b in bytes.iter_mut() {
+            carry += u32::from(*b) * 58;
+            *b = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    output.extend(std::iter::repeat_n(0, zeroes));
+    output.extend(bytes.iter().rev());
+    Ok(())
+}
+
+fn checksum(data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let hash = Sha256::digest(Sha256::digest(data));
+    let mut checksum = [0u8; CHECKSUM_LEN];
+    checksum.copy_from_slice(&hash[..CHECKSUM_LEN]);
+    checksum
+}
+
+/// Splits a Base58Check payload into its version byte and data, after verifying its checksum
+fn verify(data: &[u8]) -> Result<(u8, &[u8])> {
+    if data.len() < 1 + CHECKSUM_LEN {
+        return Err(CodecError::Truncated);
+    }
+    let (payload, expected) = data.split_at(data.len() - CHECKSUM_LEN);
+    if checksum(payload) != expected {
+        return Err(CodecError::InvalidChecksum);
+    }
+    Ok((payload[0], &payload[1..]))
+}
+
+#[derive(Clone)]
+pub struct Base58Codec {
+    alphabet: &'static [u8; 58],
+}
+
+impl Default for Base58Codec {
+    fn default() -> Self {
+        Self { alphabet: BITCOIN }
+    }
+}
+
+impl Codec for Base58Codec {
+    fn name(&self) -> &'static str {
+        "base58"
+    }
+
+    fn description(&self) -> &'static str {
+        "Base58 with bitcoin (default), ripple or flickr alphabet"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        encode_into(data, output, self.alphabet);
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        decode_into(data, output, self.alphabet)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let alphabet = if args.is_empty() {
+            BITCOIN
+        } else {
+            get_alphabet(args)?
+        };
+        Some(Box::new(Self { alphabet }) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        size * 733 / 1000 + 1
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        size * 138 / 100 + 1
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let data = data.trim_ascii();
+        !data.is_empty()
+            && data.iter().all(|b| self.alphabet.contains(b))
+            && !data.iter().all(u8::is_ascii_hexdigit)
+    }
+}
+
+#[derive(Clone)]
+pub struct Base58CheckCodec {
+    alphabet: &'static [u8; 58],
+    version: u8,
+}
+
+impl Default for Base58CheckCodec {
+    fn default() -> Self {
+        Self {
+            alphabet: BITCOIN,
+            version: 0,
+        }
+    }
+}
+
+impl Codec for Base58CheckCodec {
+    fn name(&self) -> &'static str {
+        "base58check"
+    }
+
+    fn description(&self) -> &'static str {
+        "Base58 with version byte and checksum, args: ALPHABET,version=N"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let mut payload = Vec::with_capacity(1 + data.len() + CHECKSUM_LEN);
+        payload.push(self.version);
+        payload.extend_from_slice(data);
+        let checksum = checksum(&payload[..]);
+        payload.extend_from_slice(&checksum[..]);
+        encode_into(&payload[..], output, self.alphabet);
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let mut decoded = Vec::with_capacity(self.decoded_size_hint(data.len()));
+        decode_into(data, &mut decoded, self.alphabet)?;
+        let (version, payload) = verify(&decoded[..])?;
+        eprintln!("Base58Check version byte: 0x{version:02x}");
+        output.extend_from_slice(payload);
+        Ok(())
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let mut codec = Self::default();
+        for arg in args.split(',').filter(|a| !a.is_empty()) {
+            match arg.split_once('=') {
+                Some(("version", v)) => {
+                    codec.version = match v.strip_prefix("0x") {
+                        Some(hex) => u8::from_str_radix(hex, 16).ok()?,
+                        None => v.parse().ok()?,
+                    };
+                }
+                Some(_) => return None,
+                None => codec.alphabet = get_alphabet(arg)?,
+            }
+        }
+        Some(Box::new(codec) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        size * 733 / 1000 + 1
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        (size + 1 + CHECKSUM_LEN) * 138 / 100 + 1
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let data = data.trim_ascii();
+        if !(1 + CHECKSUM_LEN..=MAX_CHECK_LEN).contains(&data.len())
+            || !data.iter().all(|b| self.alphabet.contains(b))
+        {
+            return false;
+        }
+        let mut decoded = Vec::new();
+        decode_into(data, &mut decoded, self.alphabet).is_ok() && verify(&decoded[..]).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base58_roundtrip() {
+        let codec = Base58Codec::default();
+        assert_eq!(codec.encode(b"Hello World!").unwrap(), b"2NEpo7TZRRrLZSi2U");
+        assert_eq!(codec.decode(b"2NEpo7TZRRrLZSi2U").unwrap(), b"Hello World!");
+        assert_eq!(codec.encode(b"\0\0\x01").unwrap(), b"112");
+        assert_eq!(codec.decode(b"112").unwrap(), b"\0\0\x01");
+    }
+
+    #[test]
+    fn base58check_address() {
+        let codec = Base58CheckCodec::default();
+        let hash160 = codec.decode(b"1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2").unwrap();
+        assert_eq!(hash160.len(), 20);
+        assert_eq!(
+            codec.encode(&hash160[..]).unwrap(),
+            b"1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2"
+        );
+        assert!(matches!(
+            codec.decode(b"1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3"),
+            Err(CodecError::InvalidChecksum)
+        ));
+    }
+
+    #[test]
+    fn base58check_detect() {
+        let codec = Base58CheckCodec::default();
+        assert!(codec.detect(b"1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2\n"));
+        assert!(!codec.detect(b"1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3"));
+        assert!(!codec.detect(b"1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN0"));
+
+        // Long inputs are not decoded
+        let address = codec.encode(&[0x42; 1000]).unwrap();
+        assert!(!codec.detect(&address[..]));
+    }
+}
@@ -1,10 +1,27 @@
 use base64::{
-    alphabet::{STANDARD, URL_SAFE},
-    engine::fast_portable::{FastPortable, NO_PAD, PAD},
+    alphabet::{Alphabet, BCRYPT, BIN_HEX, CRYPT, IMAP_MUTF7, STANDARD, URL_SAFE},
+    engine::{
+        fast_portable::{FastPortable, FastPortableConfig, NO_PAD, PAD},
+        DecodePaddingMode,
+    },
+    DecodeError,
 };
 use std::io::{self, copy, Read};
 
-use crate::codecs::{Codec, Result};
+use crate::codecs::{
+    stream::{transform_all, BufferedReader, Transform, TransformReader},
+    Codec, Result, Stream,
+};
+
+static STANDARD_ENGINE: FastPortable = FastPortable::from(&STANDARD, PAD);
+
+const ALPHABET_LEN: usize = 64;
+
+/// Line length of MIME encoded bodies
+const MIME_LINE_LEN: usize = 76;
+
+/// Line length of PEM files
+const PEM_LINE_LEN: usize = 64;
 
 struct StripWhitespacesReader<R> {
     inner: R,
@@ -18,96 +35,283 @@ impl<R: Read> Read for StripWhitespacesReader<R> {
                 break Ok(0);
             }
 
-            let mut i = 0;
             let mut j = 0;
-
-            // To optimize bounds checks
-            assert!(n < buf.len() && i < n);
-            while i < n {
-                if let Some(next) = buf[i..]
-                    .iter()
-                    .enumerate()
-                    .find_map(|(idx, b)| (!b.is_ascii_whitespace()).then_some(idx))
-                {
-                    buf[j] = buf[i + next];
+            for i in 0..n {
+                if !buf[i].is_ascii_whitespace() {
+                    buf[j] = buf[i];
                     j += 1;
-                    i += next + 1;
-                } else {
-                    // No more bytes
-                    break;
                 }
             }
 
-            if j > 1 {
-                break Ok(j - 1);
+            // Only whitespaces were read, try again
+            if j > 0 {
+                break Ok(j);
             }
         }
     }
 }
 
-#[derive(Default, Clone)]
-pub struct Base64StandardCodec;
-
-fn encode_into(
-    data: &[u8],
-    writer: &mut Vec<u8>,
-    alphabet: &base64::alphabet::Alphabet,
-    pad: bool,
-) -> Result<()> {
-    let engine = if pad {
-        FastPortable::from(alphabet, PAD)
-    } else {
-        FastPortable::from(alphabet, NO_PAD)
-    };
-    let mut encoder = base64::write::EncoderWriter::from(writer, &engine);
+/// Streaming encoder, works on groups of 3 bytes so that padding only appears at the end
+struct Base64Encoder<'a> {
+    engine: &'a FastPortable,
+    buffer: String,
 
-    let mut reader = data;
+    /// Length of lines, no wrapping when zero
+    wrap: usize,
+    line_break: &'static str,
 
-    copy(&mut reader, &mut encoder)?;
-    encoder.finish()?;
-    Ok(())
+    /// Length of the current line
+    column: usize,
 }
 
-fn decode_into(
-    data: &[u8],
-    writer: &mut Vec<u8>,
-    alphabet: &base64::alphabet::Alphabet,
-    pad: bool,
-) -> Result<()> {
-    let engine = if pad {
-        FastPortable::from(alphabet, PAD)
-    } else {
-        FastPortable::from(alphabet, NO_PAD)
-    };
+impl<'a> Base64Encoder<'a> {
+    fn new(engine: &'a FastPortable) -> Self {
+        Self {
+            engine,
+            buffer: String::new(),
+            wrap: 0,
+            line_break: "\n",
+            column: 0,
+        }
+    }
+}
+
+impl Transform for Base64Encoder<'_> {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        let len = if eof {
+            input.len()
+        } else {
+            input.len() - input.len() % 3
+        };
+        self.buffer.clear();
+        base64::encode_engine_string(&input[..len], &mut self.buffer, self.engine);
+        if self.wrap == 0 {
+            output.extend_from_slice(self.buffer.as_bytes());
+            return Ok(len);
+        }
+
+        let mut encoded = self.buffer.as_bytes();
+        while !encoded.is_empty() {
+            if self.column == self.wrap {
+                output.extend_from_slice(self.line_break.as_bytes());
+                self.column = 0;
+            }
+            let n = (self.wrap - self.column).min(encoded.len());
+            output.extend_from_slice(&encoded[..n]);
+            self.column += n;
+            encoded = &encoded[n..];
+        }
+        if eof && self.column > 0 {
+            output.extend_from_slice(self.line_break.as_bytes());
+        }
+        Ok(len)
+    }
+}
+
+fn encoder<'a>(reader: Stream<'a>, engine: &'a FastPortable) -> Stream<'a> {
+    Box::new(TransformReader::new(reader, Base64Encoder::new(engine)))
+}
+
+fn decoder<'a>(reader: Stream<'a>, engine: &'a FastPortable) -> Stream<'a> {
+    Box::new(base64::read::DecoderReader::from(
+        StripWhitespacesReader { inner: reader },
+        engine,
+    ))
+}
+
+fn decode_into(data: &[u8], writer: &mut Vec<u8>, engine: &FastPortable) -> Result<()> {
     let mut inner_reader = data;
     let mut reader = StripWhitespacesReader {
         inner: &mut inner_reader,
     };
-    let mut decoder = base64::read::DecoderReader::from(&mut reader, &engine);
+    let mut decoder = base64::read::DecoderReader::from(&mut reader, engine);
     copy(&mut decoder, writer)?;
     Ok(())
 }
 
+/// Splits the first argument, custom alphabets may contain commas
+fn next_arg(args: &str) -> (&str, &str) {
+    const PREFIX: &str = "alphabet=";
+    let custom_len = PREFIX.len() + ALPHABET_LEN;
+    if args.starts_with(PREFIX)
+        && args
+            .get(custom_len..)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(','))
+    {
+        let (arg, rest) = args.split_at(custom_len);
+        return (arg, rest.strip_prefix(',').unwrap_or(rest));
+    }
+    args.split_once(',').unwrap_or((args, ""))
+}
+
+fn parse_alphabet(name: &str) -> Option<Alphabet> {
+    match name {
+        "standard" => Some(STANDARD),
+        "urlsafe" => Some(URL_SAFE),
+        "crypt" => Some(CRYPT),
+        "bcrypt" => Some(BCRYPT),
+        "imap" => Some(IMAP_MUTF7),
+        "binhex" => Some(BIN_HEX),
+        _ => Alphabet::from_str(name).ok(),
+    }
+}
+
+/// Alphabet, padding and line wrapping of a Base64 codec
+struct Base64Config {
+    engine: FastPortable,
+
+    /// Symbols of the alphabet, to check the input in strict mode
+    symbols: String,
+
+    /// Length of encoded lines, no wrapping when zero
+    wrap: usize,
+    line_break: &'static str,
+
+    /// Report the first character outside of the alphabet with its offset in the input
+    strict: bool,
+}
+
+impl Base64Config {
+    fn new(alphabet: &Alphabet, config: FastPortableConfig) -> Self {
+        // `Alphabet` keeps its symbols private, encoding the values 0 to 63 in order gives them
+        let values: Vec<u32> = (0..ALPHABET_LEN as u32).collect();
+        let mut packed = Vec::with_capacity(ALPHABET_LEN / 4 * 3);
+        for group in values.chunks(4) {
+            let group = group.iter().fold(0, |acc, v| (acc << 6) | v);
+            packed.extend_from_slice(&group.to_be_bytes()[1..]);
+        }
+        let engine = FastPortable::from(alphabet, config);
+        let mut symbols = String::with_capacity(ALPHABET_LEN);
+        base64::encode_engine_string(&packed, &mut symbols, &engine);
+
+        Self {
+            engine,
+            symbols,
+            wrap: 0,
+            line_break: "\n",
+            strict: false,
+        }
+    }
+
+    fn build(args: &str, alphabet: &Alphabet, mut config: FastPortableConfig) -> Option<Self> {
+        let mut alphabet = alphabet.clone();
+        let (mut wrap, mut line_break, mut strict) = (0, "\n", false);
+        let mut rest = args;
+        while !rest.is_empty() {
+            let (arg, next) = next_arg(rest);
+            rest = next;
+            match arg.split_once('=') {
+                None if arg == "mime" => (wrap, line_break) = (MIME_LINE_LEN, "\r\n"),
+                None if arg == "pem" => wrap = PEM_LINE_LEN,
+                None if arg == "strict" => strict = true,
+                Some(("alphabet", name)) => alphabet = parse_alphabet(name)?,
+                Some(("wrap", n)) => wrap = n.parse().ok()?,
+                Some(("pad", "required")) => {
+                    config = config
+                        .with_encode_padding(true)
+                        .with_decode_padding_mode(DecodePaddingMode::RequireCanonical);
+                }
+                Some(("pad", "optional")) => {
+                    config = config.with_decode_padding_mode(DecodePaddingMode::Indifferent);
+                }
+                Some(("pad", "forbidden")) => {
+                    config = config
+                        .with_encode_padding(false)
+                        .with_decode_padding_mode(DecodePaddingMode::RequireNone);
+                }
+                _ => return None,
+            }
+        }
+
+        Some(Self {
+            wrap,
+            line_break,
+            strict,
+            ..Self::new(&alphabet, config)
+        })
+    }
+
+    /// Fails on the first character which is neither in the alphabet, padding nor a line break
+    fn check(&self, data: &[u8]) -> Result<()> {
+        let mut padding = false;
+        for (i, b) in data.iter().enumerate() {
+            match b {
+                b'=' => padding = true,
+                b'\r' | b'\n' => {}
+                _ if !padding && self.symbols.as_bytes().contains(b) => {}
+                _ => return Err(DecodeError::InvalidByte(i, *b).into()),
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(self.encoder(), data, output)
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        if self.strict {
+            self.check(data)?;
+        }
+        decode_into(data, output, &self.engine)
+    }
+
+    fn encoder(&self) -> Base64Encoder<'_> {
+        Base64Encoder {
+            wrap: self.wrap,
+            line_break: self.line_break,
+            ..Base64Encoder::new(&self.engine)
+        }
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        let len = size.div_ceil(3) * 4;
+        match self.wrap {
+            0 => len,
+            wrap => len + (len / wrap + 1) * self.line_break.len(),
+        }
+    }
+}
+
+pub struct Base64StandardCodec(Base64Config);
+
+impl Base64StandardCodec {
+    /// Lines wrapped to 64 columns, as in PEM files
+    pub fn pem() -> Self {
+        Self(Base64Config {
+            wrap: PEM_LINE_LEN,
+            ..Base64Config::new(&STANDARD, PAD)
+        })
+    }
+}
+
+impl Default for Base64StandardCodec {
+    fn default() -> Self {
+        Self(Base64Config::new(&STANDARD, PAD))
+    }
+}
+
 impl Codec for Base64StandardCodec {
     fn name(&self) -> &'static str {
         "base64-standard"
     }
 
     fn description(&self) -> &'static str {
-        "Base64 standard alphabet with padding"
+        "Base64 standard alphabet with padding, args: alphabet=NAME or 64 characters, \
+         pad=required|optional|forbidden, mime, pem, wrap=N, strict"
     }
 
     fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-        encode_into(data, output, &STANDARD, true)
+        self.0.encode_into(data, output)
     }
 
     fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-        decode_into(data, output, &STANDARD, true)
+        self.0.decode_into(data, output)
     }
 
     fn build(&self, args: &str) -> Option<super::Plugin> {
-        let _ = args;
-        Some(Box::new(Self) as super::Plugin)
+        let config = Base64Config::build(args, &STANDARD, PAD)?;
+        Some(Box::new(Self(config)) as super::Plugin)
     }
 
     fn decoded_size_hint(&self, size: usize) -> usize {
@@ -115,12 +319,29 @@ impl Codec for Base64StandardCodec {
     }
 
     fn encoded_size_hint(&self, size: usize) -> usize {
-        ((size + 3) / 3) * 4
+        self.0.encoded_size_hint(size)
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, self.0.encoder()))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        // Offsets are only meaningful over the whole input
+        if self.0.strict {
+            return Box::new(BufferedReader::new(reader, move |data| self.decode(data)));
+        }
+        decoder(reader, &self.0.engine)
     }
 }
 
-#[derive(Default, Clone)]
-pub struct Base64UrlCodec;
+pub struct Base64UrlCodec(Base64Config);
+
+impl Default for Base64UrlCodec {
+    fn default() -> Self {
+        Self(Base64Config::new(&URL_SAFE, NO_PAD))
+    }
+}
 
 impl Codec for Base64UrlCodec {
     fn name(&self) -> &'static str {
@@ -128,20 +349,20 @@ impl Codec for Base64UrlCodec {
     }
 
     fn description(&self) -> &'static str {
-        "Base64 url-safe alphabet without padding"
+        "Base64 url-safe alphabet without padding, args: same as base64-standard"
     }
 
     fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-        encode_into(data, output, &URL_SAFE, false)
+        self.0.encode_into(data, output)
     }
 
     fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-        decode_into(data, output, &URL_SAFE, false)
+        self.0.decode_into(data, output)
     }
 
     fn build(&self, args: &str) -> Option<super::Plugin> {
-        let _ = args;
-        Some(Box::new(Self) as super::Plugin)
+        let config = Base64Config::build(args, &URL_SAFE, NO_PAD)?;
+        Some(Box::new(Self(config)) as super::Plugin)
     }
 
     fn decoded_size_hint(&self, size: usize) -> usize {
@@ -149,7 +370,18 @@ impl Codec for Base64UrlCodec {
     }
 
     fn encoded_size_hint(&self, size: usize) -> usize {
-        ((size + 3) / 3) * 4
+        self.0.encoded_size_hint(size)
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, self.0.encoder()))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        if self.0.strict {
+            return Box::new(BufferedReader::new(reader, move |data| self.decode(data)));
+        }
+        decoder(reader, &self.0.engine)
     }
 }
 
@@ -166,19 +398,16 @@ impl Codec for Base64AutoCodec {
     }
 
     fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-        let std = Base64StandardCodec;
-        std.encode_into(data, output)
+        Base64StandardCodec::default().encode_into(data, output)
     }
 
     fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
         const STD_SPECIALS: &[u8] = b"/=+";
 
         if STD_SPECIALS.iter().any(|s| data.contains(s)) {
-            let std = Base64StandardCodec;
-            std.decode_into(data, output)
+            Base64StandardCodec::default().decode_into(data, output)
         } else {
-            let url = Base64UrlCodec;
-            url.decode_into(data, output)
+            Base64UrlCodec::default().decode_into(data, output)
         }
     }
 
@@ -194,11 +423,16 @@ impl Codec for Base64AutoCodec {
     fn encoded_size_hint(&self, size: usize) -> usize {
         ((size + 3) / 3) * 4
     }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        encoder(reader, &STANDARD_ENGINE)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::codecs::CodecError;
 
     #[test]
     fn strip_whitespaces() {
@@ -212,4 +446,71 @@ mod tests {
         let n = reader.read(&mut buffer[..]).unwrap();
         assert_eq!(std::str::from_utf8(&buffer[..n]).unwrap(), "Q29udGVudC1EaXNwb3NpdGlvbjogaW5saW5lOyBmaWxlbmFtZT0iaW1hZ2UwMDEuanBnQDAxRDhBQjFDLjI4QzQzMTkwLmRvY3MiOwo=");
     }
+
+    fn codec(args: &str) -> crate::codecs::Plugin {
+        Base64StandardCodec::default().build(args).unwrap()
+    }
+
+    #[test]
+    fn custom_alphabet() {
+        let shuffled = "ZYXWVUTSRQPONMLKJIHGFEDCBAzyxwvutsrqponmlkjihgfedcba9876543210+/";
+        let codec = codec(&format!("alphabet={shuffled},pad=forbidden"));
+        assert_eq!(codec.encode(b"Hello").unwrap(), b"HTEhyT1");
+        assert_eq!(codec.decode(b"HTEhyT1").unwrap(), b"Hello");
+        assert!(codec.decode(b"HTEhyT1=").is_err());
+
+        // Commas are valid symbols
+        let commas = shuffled.replace('+', ",");
+        assert!(Base64StandardCodec::default()
+            .build(&format!("alphabet={commas},strict"))
+            .is_some());
+        assert_eq!(
+            self::codec("alphabet=crypt").encode(b"Hi").unwrap(),
+            b"G4Y="
+        );
+        assert!(Base64StandardCodec::default()
+            .build("alphabet=short")
+            .is_none());
+    }
+
+    #[test]
+    fn padding() {
+        assert!(codec("pad=required").decode(b"SGk").is_err());
+        assert_eq!(codec("pad=optional").decode(b"SGk").unwrap(), b"Hi");
+        assert_eq!(codec("pad=optional").decode(b"SGk=").unwrap(), b"Hi");
+        assert_eq!(codec("pad=forbidden").encode(b"Hi").unwrap(), b"SGk");
+        let url = Base64UrlCodec::default().build("pad=required").unwrap();
+        assert_eq!(url.encode(b"Hi?").unwrap(), b"SGk_");
+        assert_eq!(url.encode(b"Hi").unwrap(), b"SGk=");
+    }
+
+    #[test]
+    fn wrapping() {
+        let data = [0u8; 60];
+        let mime = codec("mime").encode(&data).unwrap();
+        assert_eq!(&mime[76..78], b"\r\n");
+        assert!(mime.ends_with(b"AA\r\n"));
+        assert_eq!(mime.len(), 80 + 2 * 2);
+        assert_eq!(codec("wrap=4").encode(b"Hello").unwrap(), b"SGVs\nbG8=\n");
+        assert_eq!(
+            codec("pem")
+                .decode(&codec("pem").encode(&data).unwrap())
+                .unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn strict() {
+        let codec = codec("strict");
+        assert_eq!(codec.decode(b"SGVs\r\nbG8=").unwrap(), b"Hello");
+        assert!(matches!(
+            codec.decode(b"SGVs bG8="),
+            Err(CodecError::Base64(DecodeError::InvalidByte(4, b' ')))
+        ));
+        assert!(matches!(
+            codec.decode(b"SGk=SGk="),
+            Err(CodecError::Base64(DecodeError::InvalidByte(4, b'S')))
+        ));
+    }
 }
@@ -0,0 +1,487 @@
+use std::fmt::Write;
+
+use crate::codecs::{
+    stream::{transform_all, Transform, TransformReader},
+    Codec, CodecError, Result, Stream,
+};
+
+const Z85: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+const ASCII85_START: &[u8] = b"<~";
+const ASCII85_END: &[u8] = b"~>";
+const BTOA_BEGIN: &str = "xbtoa Begin";
+const BTOA_END: &str = "xbtoa End";
+const BTOA_LINE_LEN: usize = 78;
+
+/// Converts 4 bytes into 5 base 85 digits, most significant first
+fn encode_group(group: [u8; 4]) -> [u8; 5] {
+    let mut value = u32::from_be_bytes(group);
+    let mut digits = [0u8; 5];
+    for d in digits.iter_mut().rev() {
+        *d = (value % 85) as u8;
+        value /= 85;
+    }
+    digits
+}
+
+/// Converts 5 base 85 digits into 4 bytes
+fn decode_group(digits: [u8; 5]) -> Result<[u8; 4]> {
+    let value = digits.iter().fold(0u64, |acc, d| acc * 85 + u64::from(*d));
+    let value = u32::try_from(value).map_err(|_| CodecError::InvalidGroup)?;
+    Ok(value.to_be_bytes())
+}
+
+/// Accumulates digits of the `!`..`u` alphabet shared by Ascii85 and btoa
+#[derive(Default)]
+struct Ascii85Group {
+    digits: [u8; 5],
+    len: usize,
+}
+
+impl Ascii85Group {
+    fn push(&mut self, b: u8, output: &mut Vec<u8>) -> Result<()> {
+        match b {
+            b'z' if self.len == 0 => output.extend_from_slice(&[0; 4]),
+            b'!'..=b'u' => {
+                self.digits[self.len] = b - b'!';
+                self.len += 1;
+                if self.len == 5 {
+                    output.extend_from_slice(&decode_group(self.digits)?);
+                    self.len = 0;
+                }
+            }
+            _ => return Err(CodecError::InvalidChar(b)),
+        }
+        Ok(())
+    }
+
+    /// A partial group of n digits is padded with `u` and yields n - 1 bytes
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<()> {
+        match self.len {
+            0 => Ok(()),
+            1 => Err(CodecError::Truncated),
+            n => {
+                self.digits[n..].fill(84);
+                output.extend_from_slice(&decode_group(self.digits)?[..n - 1]);
+                self.len = 0;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Ascii85Encoder {
+    started: bool,
+    group: Vec<u8>,
+}
+
+impl Transform for Ascii85Encoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        if !self.started {
+            output.extend_from_slice(ASCII85_START);
+            self.started = true;
+        }
+
+        for b in input {
+            self.group.push(*b);
+            if self.group.len() == 4 {
+                let group = [self.group[0], self.group[1], self.group[2], self.group[3]];
+                if group == [0; 4] {
+                    output.push(b'z');
+                } else {
+                    output.extend(encode_group(group).iter().map(|d| d + b'!'));
+                }
+                self.group.clear();
+            }
+        }
+
+        if eof {
+            let n = self.group.len();
+            if n > 0 {
+                let mut group = [0u8; 4];
+                group[..n].copy_from_slice(&self.group[..]);
+                output.extend(encode_group(group)[..n + 1].iter().map(|d| d + b'!'));
+                self.group.clear();
+            }
+            output.extend_from_slice(ASCII85_END);
+        }
+
+        Ok(input.len())
+    }
+}
+
+#[derive(Default)]
+struct Ascii85Decoder {
+    started: bool,
+    ended: bool,
+    group: Ascii85Group,
+}
+
+impl Transform for Ascii85Decoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        let mut data = input;
+        if !self.started {
+            let trimmed = data.trim_ascii_start();
+            if trimmed.len() < ASCII85_START.len() && !eof {
+                return Ok(input.len() - trimmed.len());
+            }
+            // Delimiters are optional
+            data = trimmed.strip_prefix(ASCII85_START).unwrap_or(trimmed);
+            self.started = true;
+        }
+
+        let offset = input.len() - data.len();
+        let mut i = 0;
+        while i < data.len() {
+            let b = data[i];
+            if self.ended {
+                if !b.is_ascii_whitespace() {
+                    return Err(CodecError::InvalidChar(b));
+                }
+            } else if b == ASCII85_END[0] {
+                match data.get(i + 1) {
+                    Some(next) if *next == ASCII85_END[1] => {
+                        self.group.finish(output)?;
+                        self.ended = true;
+                        i += 1;
+                    }
+                    None if !eof => return Ok(offset + i),
+                    _ => return Err(CodecError::InvalidChar(b)),
+                }
+            } else if !b.is_ascii_whitespace() {
+                self.group.push(b, output)?;
+            }
+            i += 1;
+        }
+
+        if eof {
+            self.group.finish(output)?;
+        }
+        Ok(input.len())
+    }
+}
+
+/// Adobe's Ascii85, as found in PostScript and PDF files
+#[derive(Default, Clone)]
+pub struct Ascii85Codec;
+
+impl Codec for Ascii85Codec {
+    fn name(&self) -> &'static str {
+        "ascii85"
+    }
+
+    fn description(&self) -> &'static str {
+        "Adobe Ascii85 with <~ ~> delimiters"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(Ascii85Encoder::default(), data, output)
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(Ascii85Decoder::default(), data, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let _ = args;
+        Some(Box::new(Self) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        (size / 5) * 4
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        size.div_ceil(4) * 5 + ASCII85_START.len() + ASCII85_END.len()
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let data = data.trim_ascii();
+        data.starts_with(ASCII85_START) && data.ends_with(ASCII85_END)
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, Ascii85Encoder::default()))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, Ascii85Decoder::default()))
+    }
+}
+
+struct Z85Encoder;
+
+impl Transform for Z85Encoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        let mut groups = input.chunks_exact(4);
+        for group in groups.by_ref() {
+            let group = [group[0], group[1], group[2], group[3]];
+            output.extend(encode_group(group).iter().map(|d| Z85[usize::from(*d)]));
+        }
+        let remainder = groups.remainder().len();
+        if eof && remainder != 0 {
+            return Err(CodecError::InvalidLength(remainder));
+        }
+        Ok(input.len() - remainder)
+    }
+}
+
+#[derive(Default)]
+struct Z85Decoder {
+    digits: [u8; 5],
+    len: usize,
+}
+
+impl Transform for Z85Decoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        for b in input {
+            if b.is_ascii_whitespace() {
+                continue;
+            }
+            let digit = Z85
+                .iter()
+                .position(|s| s == b)
+                .ok_or(CodecError::InvalidChar(*b))?;
+            self.digits[self.len] = digit as u8;
+            self.len += 1;
+            if self.len == 5 {
+                output.extend_from_slice(&decode_group(self.digits)?);
+                self.len = 0;
+            }
+        }
+        if eof && self.len != 0 {
+            return Err(CodecError::Truncated);
+        }
+        Ok(input.len())
+    }
+}
+
+/// ZeroMQ's Z85, which only works on multiples of 4 bytes
+#[derive(Default, Clone)]
+pub struct Z85Codec;
+
+impl Codec for Z85Codec {
+    fn name(&self) -> &'static str {
+        "z85"
+    }
+
+    fn description(&self) -> &'static str {
+        "ZeroMQ Z85 base85 encoding"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(Z85Encoder, data, output)
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(Z85Decoder::default(), data, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let _ = args;
+        Some(Box::new(Self) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        (size / 5) * 4
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        size.div_ceil(4) * 5
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        // Z85 has no marker and its alphabet covers most of printable ASCII, plain text would be
+        // decoded by accident
+        let _ = data;
+        false
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, Z85Encoder))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, Z85Decoder::default()))
+    }
+}
+
+/// Running checksums of the btoa trailer
+#[derive(Default, PartialEq, Eq)]
+struct BtoaChecksum {
+    eor: u32,
+    sum: u32,
+    rot: u32,
+}
+
+impl BtoaChecksum {
+    fn update(&mut self, data: &[u8]) {
+        for b in data {
+            let b = u32::from(*b);
+            self.eor ^= b;
+            self.sum = self.sum.wrapping_add(b + 1);
+            self.rot = self.rot.rotate_left(1).wrapping_add(b);
+        }
+    }
+}
+
+/// Parses `xbtoa End N <len> <len hex> E <eor> S <sum> R <rot>`
+fn parse_btoa_trailer(line: &str) -> Option<(usize, BtoaChecksum)> {
+    let fields: Vec<&str> = line.strip_prefix(BTOA_END)?.split_whitespace().collect();
+    match fields[..] {
+        ["N", len, _, "E", eor, "S", sum, "R", rot] => Some((
+            len.parse().ok()?,
+            BtoaChecksum {
+                eor: u32::from_str_radix(eor, 16).ok()?,
+                sum: u32::from_str_radix(sum, 16).ok()?,
+                rot: u32::from_str_radix(rot, 16).ok()?,
+            },
+        )),
+        _ => None,
+    }
+}
+
+/// Output of the btoa tool, Ascii85 without delimiters but with a header and trailer
+#[derive(Default, Clone)]
+pub struct BtoaCodec;
+
+impl Codec for BtoaCodec {
+    fn name(&self) -> &'static str {
+        "btoa"
+    }
+
+    fn description(&self) -> &'static str {
+        "btoa base85 with xbtoa header and trailer"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        output.extend_from_slice(BTOA_BEGIN.as_bytes());
+        output.push(b'\n');
+
+        // Last group is padded with zeroes, the trailer has the real length
+        let mut line_len = 0;
+        for chunk in data.chunks(4) {
+            let mut group = [0u8; 4];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let encoded = match group {
+                [0, 0, 0, 0] => &b"z"[..],
+                [b' ', b' ', b' ', b' '] => &b"y"[..],
+                _ => &encode_group(group).map(|d| d + b'!')[..],
+            };
+            if line_len + encoded.len() > BTOA_LINE_LEN {
+                output.push(b'\n');
+                line_len = 0;
+            }
+            output.extend_from_slice(encoded);
+            line_len += encoded.len();
+        }
+        if line_len > 0 {
+            output.push(b'\n');
+        }
+
+        let mut checksum = BtoaChecksum::default();
+        checksum.update(data);
+        let mut trailer = String::new();
+        writeln!(
+            &mut trailer,
+            "{BTOA_END} N {len} {len:x} E {eor:x} S {sum:x} R {rot:x}",
+            len = data.len(),
+            eor = checksum.eor,
+            sum = checksum.sum,
+            rot = checksum.rot
+        )
+        .unwrap();
+        output.extend_from_slice(trailer.as_bytes());
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let text = std::str::from_utf8(data)?;
+        let mut lines = text.lines().skip_while(|l| !l.starts_with(BTOA_BEGIN));
+        if lines.next().is_none() {
+            return Err(CodecError::MissingHeader);
+        }
+
+        let start = output.len();
+        let mut group = Ascii85Group::default();
+        for line in lines {
+            if line.starts_with(BTOA_END) {
+                group.finish(output)?;
+                let (len, expected) = parse_btoa_trailer(line).ok_or(CodecError::MissingTrailer)?;
+                if output.len() - start < len {
+                    return Err(CodecError::Truncated);
+                }
+                output.truncate(start + len);
+
+                let mut checksum = BtoaChecksum::default();
+                checksum.update(&output[start..]);
+                if checksum != expected {
+                    return Err(CodecError::InvalidChecksum);
+                }
+                return Ok(());
+            }
+
+            for b in line.trim_end().bytes() {
+                if b == b'y' && group.len == 0 {
+                    output.extend_from_slice(b"    ");
+                } else {
+                    group.push(b, output)?;
+                }
+            }
+        }
+
+        Err(CodecError::MissingTrailer)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let _ = args;
+        Some(Box::new(Self) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        (size / 5) * 4
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        size.div_ceil(4) * 5 + 128
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        data.trim_ascii_start().starts_with(BTOA_BEGIN.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii85() {
+        let codec = Ascii85Codec;
+        assert_eq!(codec.encode(b"Man sure.").unwrap(), b"<~9jqo^F*2M7/c~>");
+        assert_eq!(codec.decode(b"<~9jqo^F*2M7/c~>\n").unwrap(), b"Man sure.");
+        assert_eq!(codec.decode(b"9jqo^\nF*2M7/c").unwrap(), b"Man sure.");
+        assert_eq!(codec.encode(&[0; 5]).unwrap(), b"<~z!!~>");
+        assert_eq!(codec.decode(b"<~z!!~>").unwrap(), [0; 5]);
+    }
+
+    #[test]
+    fn z85() {
+        let key = [0x86, 0x4f, 0xd2, 0x6f, 0xb5, 0x59, 0xf7, 0x5b];
+        let codec = Z85Codec;
+        assert_eq!(codec.encode(&key[..]).unwrap(), b"HelloWorld");
+        assert_eq!(codec.decode(b"HelloWorld").unwrap(), key);
+        assert!(codec.encode(b"abc").is_err());
+    }
+
+    #[test]
+    fn btoa_roundtrip() {
+        let codec = BtoaCodec;
+        let data = b"btoa keeps the length    in its trailer\0\0\0\0!";
+        let encoded = codec.encode(&data[..]).unwrap();
+        assert!(codec.detect(&encoded[..]));
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), data);
+    }
+}
@@ -0,0 +1,435 @@
+use std::collections::HashMap;
+
+use encoding_rs::{EncoderResult, Encoding};
+
+use crate::codecs::{Codec, CodecError, Result};
+
+/// EBCDIC code page 037 (US/Canada), each byte maps to a Latin-1 code point
+const CP037: [u8; 256] = [
+    0x00, 0x01, 0x02, 0x03, 0x9c, 0x09, 0x86, 0x7f, 0x97, 0x8d, 0x8e, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x9d, 0x85, 0x08, 0x87, 0x18, 0x19, 0x92, 0x8f, 0x1c, 0x1d, 0x1e, 0x1f,
+    0x80, 0x81, 0x82, 0x83, 0x84, 0x0a, 0x17, 0x1b, 0x88, 0x89, 0x8a, 0x8b, 0x8c, 0x05, 0x06, 0x07,
+    0x90, 0x91, 0x16, 0x93, 0x94, 0x95, 0x96, 0x04, 0x98, 0x99, 0x9a, 0x9b, 0x14, 0x15, 0x9e, 0x1a,
+    0x20, 0xa0, 0xe2, 0xe4, 0xe0, 0xe1, 0xe3, 0xe5, 0xe7, 0xf1, 0xa2, 0x2e, 0x3c, 0x28, 0x2b, 0x7c,
+    0x26, 0xe9, 0xea, 0xeb, 0xe8, 0xed, 0xee, 0xef, 0xec, 0xdf, 0x21, 0x24, 0x2a, 0x29, 0x3b, 0xac,
+    0x2d, 0x2f, 0xc2, 0xc4, 0xc0, 0xc1, 0xc3, 0xc5, 0xc7, 0xd1, 0xa6, 0x2c, 0x25, 0x5f, 0x3e, 0x3f,
+    0xf8, 0xc9, 0xca, 0xcb, 0xc8, 0xcd, 0xce, 0xcf, 0xcc, 0x60, 0x3a, 0x23, 0x40, 0x27, 0x3d, 0x22,
+    0xd8, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0xab, 0xbb, 0xf0, 0xfd, 0xfe, 0xb1,
+    0xb0, 0x6a, 0x6b, 0x6c, 0x6d, 0x6e, 0x6f, 0x70, 0x71, 0x72, 0xaa, 0xba, 0xe6, 0xb8, 0xc6, 0xa4,
+    0xb5, 0x7e, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0xa1, 0xbf, 0xd0, 0xdd, 0xde, 0xae,
+    0x5e, 0xa3, 0xa5, 0xb7, 0xa9, 0xa7, 0xb6, 0xbc, 0xbd, 0xbe, 0x5b, 0x5d, 0xaf, 0xa8, 0xb4, 0xd7,
+    0x7b, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0xad, 0xf4, 0xf6, 0xf2, 0xf3, 0xf5,
+    0x7d, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f, 0x50, 0x51, 0x52, 0xb9, 0xfb, 0xfc, 0xf9, 0xfa, 0xff,
+    0x5c, 0xf7, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0xb2, 0xd4, 0xd6, 0xd2, 0xd3, 0xd5,
+    0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0xb3, 0xdb, 0xdc, 0xd9, 0xda, 0x9f,
+];
+
+// Other EBCDIC code pages, as differences from code page 037
+const CP273: &[(u8, char)] = &[
+    (0x43, '{'),
+    (0x4a, 'Ä'),
+    (0x4f, '!'),
+    (0x59, '~'),
+    (0x5a, 'Ü'),
+    (0x5f, '^'),
+    (0x63, '['),
+    (0x6a, 'ö'),
+    (0x7c, '§'),
+    (0xa1, 'ß'),
+    (0xb0, '¢'),
+    (0xb5, '@'),
+    (0xba, '¬'),
+    (0xbb, '|'),
+    (0xbc, '‾'),
+    (0xc0, 'ä'),
+    (0xcc, '¦'),
+    (0xd0, 'ü'),
+    (0xdc, '}'),
+    (0xe0, 'Ö'),
+    (0xec, '\\'),
+    (0xfc, ']'),
+];
+const CP500: &[(u8, char)] = &[
+    (0x4a, '['),
+    (0x4f, '!'),
+    (0x5a, ']'),
+    (0x5f, '^'),
+    (0xb0, '¢'),
+    (0xba, '¬'),
+    (0xbb, '|'),
+];
+const CP1140: &[(u8, char)] = &[(0x9f, '€')];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    /// Byte order of UTF-16 or UTF-32 text without BOM, ASCII characters have their zero bytes
+    /// last in little endian
+    fn guess(data: &[u8], unit_len: usize) -> Self {
+        let (mut first, mut last) = (0, 0);
+        for unit in data.chunks_exact(unit_len) {
+            first += usize::from(unit[0] == 0);
+            last += usize::from(unit[unit_len - 1] == 0);
+        }
+        if first > last {
+            Self::Big
+        } else {
+            Self::Little
+        }
+    }
+
+    fn read_u16(self, unit: &[u8]) -> u16 {
+        let unit = [unit[0], unit[1]];
+        match self {
+            Self::Little => u16::from_le_bytes(unit),
+            Self::Big => u16::from_be_bytes(unit),
+        }
+    }
+
+    fn read_u32(self, unit: &[u8]) -> u32 {
+        let unit = [unit[0], unit[1], unit[2], unit[3]];
+        match self {
+            Self::Little => u32::from_le_bytes(unit),
+            Self::Big => u32::from_be_bytes(unit),
+        }
+    }
+
+    fn write_u16(self, unit: u16, output: &mut Vec<u8>) {
+        match self {
+            Self::Little => output.extend_from_slice(&unit.to_le_bytes()),
+            Self::Big => output.extend_from_slice(&unit.to_be_bytes()),
+        }
+    }
+
+    fn write_u32(self, unit: u32, output: &mut Vec<u8>) {
+        match self {
+            Self::Little => output.extend_from_slice(&unit.to_le_bytes()),
+            Self::Big => output.extend_from_slice(&unit.to_be_bytes()),
+        }
+    }
+}
+
+/// Removes the byte order mark of UTF-16 or UTF-32 text, and finds the byte order when not given
+fn strip_bom(data: &[u8], endian: Option<Endian>, unit_len: usize) -> (Endian, &[u8]) {
+    let bom: &[u8] = if unit_len == 2 {
+        &[0xff, 0xfe]
+    } else {
+        &[0xff, 0xfe, 0, 0]
+    };
+    let reversed: Vec<u8> = bom.iter().rev().copied().collect();
+
+    if endian != Some(Endian::Big)
+        && let Some(rest) = data.strip_prefix(bom)
+    {
+        (Endian::Little, rest)
+    } else if endian != Some(Endian::Little)
+        && let Some(rest) = data.strip_prefix(&reversed[..])
+    {
+        (Endian::Big, rest)
+    } else {
+        (
+            endian.unwrap_or_else(|| Endian::guess(data, unit_len)),
+            data,
+        )
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Charset {
+    Utf8,
+    /// Byte order is given by the BOM when not set
+    Utf16(Option<Endian>),
+    Utf32(Option<Endian>),
+    Latin1,
+    /// Differences from code page 037
+    Ebcdic(&'static [(u8, char)]),
+    Other(&'static Encoding),
+}
+
+impl Charset {
+    fn parse(name: &str) -> Option<Self> {
+        let normalized: String = name
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_'))
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        let charset = match normalized.as_str() {
+            "utf8" => Self::Utf8,
+            "utf16" => Self::Utf16(None),
+            "utf16le" => Self::Utf16(Some(Endian::Little)),
+            "utf16be" => Self::Utf16(Some(Endian::Big)),
+            "utf32" => Self::Utf32(None),
+            "utf32le" => Self::Utf32(Some(Endian::Little)),
+            "utf32be" => Self::Utf32(Some(Endian::Big)),
+            // Labels mapped to Windows-1252 by `encoding_rs`
+            "latin1" | "iso88591" => Self::Latin1,
+            "ebcdic" | "cp037" | "ibm037" => Self::Ebcdic(&[]),
+            "cp273" | "ibm273" => Self::Ebcdic(CP273),
+            "cp500" | "ibm500" => Self::Ebcdic(CP500),
+            "cp1140" | "ibm1140" => Self::Ebcdic(CP1140),
+            _ => Self::Other(Encoding::for_label(name.as_bytes())?),
+        };
+        Some(charset)
+    }
+
+    fn ebcdic_table(patches: &[(u8, char)]) -> [char; 256] {
+        let mut table = CP037.map(char::from);
+        for (b, c) in patches {
+            table[usize::from(*b)] = *c;
+        }
+        table
+    }
+
+    fn decode(self, data: &[u8]) -> Result<String> {
+        let text = match self {
+            Self::Utf8 => {
+                let data = data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(data);
+                std::str::from_utf8(data)?.to_owned()
+            }
+            Self::Utf16(endian) => {
+                let (endian, data) = strip_bom(data, endian, 2);
+                if data.len() % 2 != 0 {
+                    return Err(CodecError::InvalidLength(data.len() % 2));
+                }
+                let units = data.chunks_exact(2).map(|unit| endian.read_u16(unit));
+                char::decode_utf16(units)
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(|_| CodecError::Malformed("Unpaired UTF-16 surrogate"))?
+            }
+            Self::Utf32(endian) => {
+                let (endian, data) = strip_bom(data, endian, 4);
+                if data.len() % 4 != 0 {
+                    return Err(CodecError::InvalidLength(data.len() % 4));
+                }
+                data.chunks_exact(4)
+                    .map(|unit| char::from_u32(endian.read_u32(unit)))
+                    .collect::<Option<_>>()
+                    .ok_or(CodecError::Malformed("Invalid UTF-32 code point"))?
+            }
+            Self::Latin1 => data.iter().copied().map(char::from).collect(),
+            Self::Ebcdic(patches) => {
+                let table = Self::ebcdic_table(patches);
+                data.iter().map(|b| table[usize::from(*b)]).collect()
+            }
+            Self::Other(encoding) => encoding
+                .decode_without_bom_handling_and_without_replacement(data)
+                .ok_or(CodecError::Malformed(
+                    "Invalid byte sequence for the charset",
+                ))?
+                .into_owned(),
+        };
+        Ok(text)
+    }
+
+    fn encode(self, text: &str, output: &mut Vec<u8>) -> Result<()> {
+        match self {
+            Self::Utf8 => output.extend_from_slice(text.as_bytes()),
+            Self::Utf16(endian) => {
+                // Without an explicit byte order, a BOM tells it
+                let endian = endian.unwrap_or_else(|| {
+                    Endian::Little.write_u16(0xfeff, output);
+                    Endian::Little
+                });
+                for unit in text.encode_utf16() {
+                    endian.write_u16(unit, output);
+                }
+            }
+            Self::Utf32(endian) => {
+                let endian = endian.unwrap_or_else(|| {
+                    Endian::Little.write_u32(0xfeff, output);
+                    Endian::Little
+                });
+                for c in text.chars() {
+                    endian.write_u32(u32::from(c), output);
+                }
+            }
+            Self::Latin1 => {
+                for c in text.chars() {
+                    output.push(u8::try_from(c).map_err(|_| CodecError::UnmappableChar(c))?);
+                }
+            }
+            Self::Ebcdic(patches) => {
+                let table: HashMap<char, u8> = Self::ebcdic_table(patches)
+                    .into_iter()
+                    .zip(0..=u8::MAX)
+                    .collect();
+                for c in text.chars() {
+                    output.push(*table.get(&c).ok_or(CodecError::UnmappableChar(c))?);
+                }
+            }
+            Self::Other(encoding) => {
+                let mut encoder = encoding.new_encoder();
+                let mut text = text;
+                loop {
+                    if let Some(len) =
+                        encoder.max_buffer_length_from_utf8_without_replacement(text.len())
+                    {
+                        output.reserve(len);
+                    }
+                    let (result, read) =
+                        encoder.encode_from_utf8_to_vec_without_replacement(text, output, true);
+                    text = &text[read..];
+                    match result {
+                        EncoderResult::InputEmpty => break,
+                        EncoderResult::OutputFull => output.reserve(text.len() + 16),
+                        EncoderResult::Unmappable(c) => {
+                            return Err(CodecError::UnmappableChar(c));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Converts text between character sets
+#[derive(Clone)]
+pub struct CharsetCodec {
+    from: Charset,
+    to: Charset,
+}
+
+impl Default for CharsetCodec {
+    fn default() -> Self {
+        Self {
+            from: Charset::Utf16(None),
+            to: Charset::Utf8,
+        }
+    }
+}
+
+impl Codec for CharsetCodec {
+    fn name(&self) -> &'static str {
+        "charset"
+    }
+
+    fn description(&self) -> &'static str {
+        "Converts text between charsets, args: FROM>TO (default utf-16>utf-8) with utf-16le, \
+         utf-32be, latin1, iso-8859-15, windows-1251, cp037, ..."
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        self.from.encode(&self.to.decode(data)?, output)
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        self.to.encode(&self.from.decode(data)?, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let codec = match args.split_once('>') {
+            _ if args.is_empty() => Self::default(),
+            Some((from, to)) => Self {
+                from: Charset::parse(from)?,
+                to: Charset::parse(to)?,
+            },
+            None => Self {
+                from: Charset::parse(args)?,
+                to: Charset::Utf8,
+            },
+        };
+        Some(Box::new(codec) as super::Plugin)
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let Charset::Utf16(endian) = self.from else {
+            return false;
+        };
+        if data.len() < 2 || !data.len().is_multiple_of(2) {
+            return false;
+        }
+        let (_, text) = strip_bom(data, endian, 2);
+        if text.len() != data.len() {
+            return true;
+        }
+
+        // Most code units of ASCII text have exactly one zero byte
+        let units = data.len() / 2;
+        let ascii_units = data
+            .chunks_exact(2)
+            .filter(|unit| (unit[0] == 0) != (unit[1] == 0))
+            .count();
+        ascii_units * 4 >= units * 3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codec(args: &str) -> crate::codecs::Plugin {
+        CharsetCodec::default().build(args).unwrap()
+    }
+
+    #[test]
+    fn utf16() {
+        // PowerShell -EncodedCommand
+        let command = b"w\0h\0o\0a\0m\0i\0";
+        assert!(CharsetCodec::default().detect(command));
+        assert_eq!(CharsetCodec::default().decode(command).unwrap(), b"whoami");
+        assert_eq!(
+            codec("utf-16")
+                .decode(b"\xfe\xff\0h\xd8\x3d\xde\x00")
+                .unwrap(),
+            "h😀".as_bytes()
+        );
+        assert_eq!(codec("utf-16le>utf-8").encode(b"id").unwrap(), b"i\0d\0");
+        assert!(codec("utf-16le").decode(b"\x3d\xd8").is_err());
+        assert!(!CharsetCodec::default().detect(b"whoami"));
+    }
+
+    #[test]
+    fn utf32() {
+        let codec = codec("UTF-32");
+        let encoded = codec.encode("é😀".as_bytes()).unwrap();
+        assert_eq!(encoded, b"\xff\xfe\0\0\xe9\0\0\0\x00\xf6\x01\0");
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), "é😀".as_bytes());
+        assert_eq!(codec.decode(b"\0\0\0A").unwrap(), b"A");
+    }
+
+    #[test]
+    fn single_byte() {
+        assert_eq!(
+            codec("latin1").decode(b"caf\xe9").unwrap(),
+            "café".as_bytes()
+        );
+        assert_eq!(
+            codec("windows-1252").decode(b"\x80").unwrap(),
+            "€".as_bytes()
+        );
+        assert_eq!(
+            codec("iso-8859-15>windows-1252").decode(b"\xa4").unwrap(),
+            b"\x80"
+        );
+        assert!(matches!(
+            codec("latin1").encode("€".as_bytes()),
+            Err(CodecError::UnmappableChar('€'))
+        ));
+        assert_eq!(
+            codec("koi8-r").encode("привет".as_bytes()).unwrap(),
+            b"\xd0\xd2\xc9\xd7\xc5\xd4"
+        );
+    }
+
+    #[test]
+    fn ebcdic() {
+        assert_eq!(
+            codec("ebcdic").decode(b"\xc8\x85\x93\x93\x96\x5a").unwrap(),
+            b"Hello!"
+        );
+        assert_eq!(codec("cp500").decode(b"\x5a").unwrap(), b"]");
+        assert_eq!(
+            codec("cp1140>utf-16be").decode(b"\x9f").unwrap(),
+            b"\x20\xac"
+        );
+        assert_eq!(
+            codec("cp037").encode(b"Hello!").unwrap(),
+            b"\xc8\x85\x93\x93\x96\x5a"
+        );
+    }
+}
@@ -0,0 +1,649 @@
+use std::collections::HashMap;
+
+use crate::codecs::{
+    score::{chi_squared, index_of_coincidence, letter_counts, reads_better},
+    stream::{transform_all, BufferedReader, ByteMap, Transform, TransformReader},
+    Codec, CodecError, Result, Stream,
+};
+
+/// Longest Vigenère key looked for by default
+const DEFAULT_MAX_KEY_LEN: usize = 20;
+
+/// Columns of a text encrypted with the right key length have about the index of coincidence of
+/// English text, which is 0.066
+const ENGLISH_IC_THRESHOLD: f64 = 0.055;
+
+/// Number of Kasiski factors shown when recovering a key
+const KASISKI_FACTORS: usize = 5;
+
+const DEFAULT_RAILS: usize = 3;
+
+/// Affine key used when none is given, the usual textbook example
+const DEFAULT_AFFINE_KEY: (u32, u32) = (5, 8);
+
+/// Applies `f` to the index of a letter in the alphabet, keeping its case
+fn map_letter(b: u8, f: impl FnOnce(u32) -> u32) -> u8 {
+    match b {
+        b'a'..=b'z' => (f(u32::from(b - b'a')) % 26) as u8 + b'a',
+        b'A'..=b'Z' => (f(u32::from(b - b'A')) % 26) as u8 + b'A',
+        _ => b,
+    }
+}
+
+fn parse_key(key: &str) -> Option<Vec<u8>> {
+    if key.is_empty() || !key.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some(key.bytes().map(|b| b.to_ascii_lowercase() - b'a').collect())
+}
+
+#[derive(Clone)]
+enum VigenereMode {
+    /// Shift of each letter of the key
+    Key(Vec<u8>),
+
+    /// Guesses a key up to the given length
+    Recover(usize),
+}
+
+/// Vigenère cipher, only letters use up the key
+#[derive(Clone)]
+pub struct VigenereCodec {
+    mode: VigenereMode,
+}
+
+impl Default for VigenereCodec {
+    fn default() -> Self {
+        Self {
+            mode: VigenereMode::Recover(DEFAULT_MAX_KEY_LEN),
+        }
+    }
+}
+
+struct VigenereTransform<'a> {
+    shifts: &'a [u8],
+    position: usize,
+    decode: bool,
+}
+
+impl<'a> VigenereTransform<'a> {
+    fn new(shifts: &'a [u8], decode: bool) -> Self {
+        Self {
+            shifts,
+            position: 0,
+            decode,
+        }
+    }
+}
+
+impl Transform for VigenereTransform<'_> {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, _eof: bool) -> Result<usize> {
+        for b in input {
+            if !b.is_ascii_alphabetic() {
+                output.push(*b);
+                continue;
+            }
+            let shift = u32::from(self.shifts[self.position]);
+            let shift = if self.decode { 26 - shift } else { shift };
+            output.push(map_letter(*b, |x| x + shift));
+            self.position = (self.position + 1) % self.shifts.len();
+        }
+        Ok(input.len())
+    }
+}
+
+/// Counts how many distances between repeated trigrams each key length divides
+fn kasiski(letters: &[u8], max_len: usize) -> Vec<usize> {
+    let mut factors = vec![0; max_len + 1];
+    let mut last_seen = HashMap::new();
+    for (i, trigram) in letters.windows(3).enumerate() {
+        if let Some(previous) = last_seen.insert(trigram, i) {
+            let distance = i - previous;
+            for (len, count) in factors.iter_mut().enumerate().skip(2) {
+                if distance.is_multiple_of(len) {
+                    *count += 1;
+                }
+            }
+        }
+    }
+    factors
+}
+
+/// Average index of coincidence of the columns for a key of length `len`
+fn columns_ic(letters: &[u8], len: usize) -> f64 {
+    (0..len)
+        .map(|column| {
+            let column: Vec<u8> = letters.iter().skip(column).step_by(len).copied().collect();
+            index_of_coincidence(&letter_counts(&column[..]))
+        })
+        .sum::<f64>()
+        / len as f64
+}
+
+/// Shift making `column` the closest to English letter frequencies
+fn best_shift(column: &[u8]) -> u8 {
+    let counts = letter_counts(column);
+    (0..26)
+        .map(|shift| {
+            let mut unshifted = [0; 26];
+            for (i, n) in unshifted.iter_mut().enumerate() {
+                *n = counts[(i + shift) % 26];
+            }
+            (shift as u8, chi_squared(&unshifted))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(0, |(shift, _)| shift)
+}
+
+fn recover_key(data: &[u8], max_len: usize) -> Vec<u8> {
+    let letters: Vec<u8> = data
+        .iter()
+        .filter(|b| b.is_ascii_alphabetic())
+        .map(u8::to_ascii_lowercase)
+        .collect();
+    let max_len = max_len.min(letters.len() / 2).max(1);
+
+    let factors = kasiski(&letters[..], max_len);
+    let mut ranked: Vec<_> = (2..=max_len).filter(|len| factors[*len] > 0).collect();
+    ranked.sort_by_key(|len| std::cmp::Reverse(factors[*len]));
+    if !ranked.is_empty() {
+        let ranked: Vec<_> = ranked
+            .iter()
+            .take(KASISKI_FACTORS)
+            .map(|len| format!("{len} ({})", factors[*len]))
+            .collect();
+        eprintln!("Kasiski factors: {}", ranked.join(", "));
+    }
+
+    // Multiples of the key length also look like English, so keep the shortest one
+    let ics: Vec<_> = (1..=max_len)
+        .map(|len| (len, columns_ic(&letters[..], len)))
+        .collect();
+    let (key_len, ic) = ics
+        .iter()
+        .copied()
+        .find(|(len, ic)| {
+            *ic >= ENGLISH_IC_THRESHOLD && (ranked.is_empty() || *len == 1 || factors[*len] > 0)
+        })
+        .or_else(|| ics.iter().copied().max_by(|a, b| a.1.total_cmp(&b.1)))
+        .unwrap_or((1, 0.));
+    eprintln!("Key length {key_len} (index of coincidence {ic:.3})");
+
+    (0..key_len)
+        .map(|column| {
+            let column: Vec<u8> = letters
+                .iter()
+                .skip(column)
+                .step_by(key_len)
+                .copied()
+                .collect();
+            best_shift(&column[..])
+        })
+        .collect()
+}
+
+impl Codec for VigenereCodec {
+    fn name(&self) -> &'static str {
+        "vigenere"
+    }
+
+    fn description(&self) -> &'static str {
+        "Vigenère cipher, args: KEY or recover[=MAXLEN] (default)"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        match self.mode {
+            VigenereMode::Key(ref shifts) => {
+                transform_all(VigenereTransform::new(shifts, false), data, output)
+            }
+            VigenereMode::Recover(_) => Err(CodecError::MissingKey),
+        }
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let shifts = match self.mode {
+            VigenereMode::Key(ref shifts) => shifts.clone(),
+            VigenereMode::Recover(max_len) => {
+                let shifts = recover_key(data, max_len);
+                let key: String = shifts.iter().map(|s| char::from(s + b'A')).collect();
+                eprintln!("Recovered key: {key}");
+                shifts
+            }
+        };
+        transform_all(VigenereTransform::new(&shifts[..], true), data, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let mode = match args.split_once('=') {
+            None => match args {
+                "" | "recover" => VigenereMode::Recover(DEFAULT_MAX_KEY_LEN),
+                key => VigenereMode::Key(parse_key(key)?),
+            },
+            Some(("recover", max_len)) => {
+                VigenereMode::Recover(max_len.parse().ok().filter(|l| *l > 0)?)
+            }
+            _ => return None,
+        };
+        Some(Box::new(Self { mode }) as super::Plugin)
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        // Any text is a valid ciphertext
+        let _ = data;
+        false
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        match self.mode {
+            VigenereMode::Key(ref shifts) => Box::new(TransformReader::new(
+                reader,
+                VigenereTransform::new(shifts, false),
+            )),
+            VigenereMode::Recover(_) => {
+                Box::new(BufferedReader::new(reader, move |data| self.encode(data)))
+            }
+        }
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        match self.mode {
+            VigenereMode::Key(ref shifts) => Box::new(TransformReader::new(
+                reader,
+                VigenereTransform::new(shifts, true),
+            )),
+            // Needs the whole input to guess the key
+            VigenereMode::Recover(_) => {
+                Box::new(BufferedReader::new(reader, move |data| self.decode(data)))
+            }
+        }
+    }
+}
+
+/// Atbash, reverses the alphabet
+#[derive(Default, Clone)]
+pub struct AtbashCodec;
+
+impl AtbashCodec {
+    fn apply(b: u8) -> u8 {
+        map_letter(b, |x| 25 - x)
+    }
+}
+
+impl Codec for AtbashCodec {
+    fn name(&self) -> &'static str {
+        "atbash"
+    }
+
+    fn description(&self) -> &'static str {
+        "Atbash cipher, reverses the alphabet"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        output.extend(data.iter().copied().map(Self::apply));
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        // Atbash is its own inverse
+        self.encode_into(data, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let _ = args;
+        Some(Box::new(Self) as super::Plugin)
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let decoded: Vec<u8> = data.iter().copied().map(Self::apply).collect();
+        reads_better(data, &decoded[..])
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, ByteMap(Self::apply)))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, ByteMap(Self::apply)))
+    }
+}
+
+/// Affine cipher, letters are encrypted with `a * x + b`
+#[derive(Clone)]
+pub struct AffineCodec {
+    a: u32,
+    b: u32,
+}
+
+impl Default for AffineCodec {
+    fn default() -> Self {
+        let (a, b) = DEFAULT_AFFINE_KEY;
+        Self { a, b }
+    }
+}
+
+impl AffineCodec {
+    /// Modular inverse of `a`, which is coprime with 26
+    fn inverse(&self) -> u32 {
+        (1..26).find(|i| (self.a * i) % 26 == 1).unwrap()
+    }
+}
+
+impl Codec for AffineCodec {
+    fn name(&self) -> &'static str {
+        "affine"
+    }
+
+    fn description(&self) -> &'static str {
+        "Affine cipher, args: A,B (default 5,8), A being coprime with 26"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        output.extend(data.iter().map(|c| map_letter(*c, |x| self.a * x + self.b)));
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let a_inv = self.inverse();
+        output.extend(
+            data.iter()
+                .map(|c| map_letter(*c, |x| a_inv * (x + 26 - self.b))),
+        );
+        Ok(())
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let (a, b) = match args.split_once(',') {
+            None if args.is_empty() => DEFAULT_AFFINE_KEY,
+            Some((a, b)) => (a.parse().ok()?, b.parse().ok()?),
+            None => return None,
+        };
+        if a >= 26 || b >= 26 || a % 2 == 0 || a == 13 {
+            return None;
+        }
+        Some(Box::new(Self { a, b }) as super::Plugin)
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let _ = data;
+        false
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        let (a, b) = (self.a, self.b);
+        Box::new(TransformReader::new(
+            reader,
+            ByteMap(move |c| map_letter(c, |x| a * x + b)),
+        ))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        let (a_inv, b) = (self.inverse(), self.b);
+        Box::new(TransformReader::new(
+            reader,
+            ByteMap(move |c| map_letter(c, |x| a_inv * (x + 26 - b))),
+        ))
+    }
+}
+
+/// Indices of the items in the order they are written in, rail after rail
+fn rail_order(len: usize, rails: usize) -> Vec<usize> {
+    let cycle = 2 * (rails - 1);
+    let mut order = Vec::with_capacity(len);
+    for rail in 0..rails {
+        order.extend((0..len).filter(|i| i % cycle == rail || i % cycle == cycle - rail));
+    }
+    order
+}
+
+fn rail_fence<T: Copy>(items: &[T], rails: usize, decode: bool) -> Vec<T> {
+    let order = rail_order(items.len(), rails);
+    if !decode {
+        return order.iter().map(|i| items[*i]).collect();
+    }
+
+    let mut output = items.to_vec();
+    for (item, i) in items.iter().zip(order) {
+        output[i] = *item;
+    }
+    output
+}
+
+/// Rail fence transposition, of characters when the input is valid UTF-8 and of bytes otherwise
+#[derive(Clone)]
+pub struct RailFenceCodec {
+    rails: usize,
+}
+
+impl Default for RailFenceCodec {
+    fn default() -> Self {
+        Self {
+            rails: DEFAULT_RAILS,
+        }
+    }
+}
+
+impl RailFenceCodec {
+    fn transpose(&self, data: &[u8], output: &mut Vec<u8>, decode: bool) {
+        match std::str::from_utf8(data) {
+            Ok(text) => {
+                let chars: Vec<char> = text.chars().collect();
+                let transposed: String = rail_fence(&chars[..], self.rails, decode)
+                    .into_iter()
+                    .collect();
+                output.extend_from_slice(transposed.as_bytes());
+            }
+            Err(_) => output.extend(rail_fence(data, self.rails, decode)),
+        }
+    }
+}
+
+impl Codec for RailFenceCodec {
+    fn name(&self) -> &'static str {
+        "rail-fence"
+    }
+
+    fn description(&self) -> &'static str {
+        "Rail fence cipher, args: RAILS (default 3)"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        self.transpose(data, output, false);
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        self.transpose(data, output, true);
+        Ok(())
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let rails = if args.is_empty() {
+            DEFAULT_RAILS
+        } else {
+            args.parse().ok().filter(|r| *r >= 2)?
+        };
+        Some(Box::new(Self { rails }) as super::Plugin)
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let _ = data;
+        false
+    }
+}
+
+/// Bacon's cipher, each letter is written as 5 `A` or `B`
+#[derive(Default, Clone)]
+pub struct BaconCodec {
+    /// Original 24 letters alphabet, where I/J and U/V share the same code
+    classic: bool,
+}
+
+impl BaconCodec {
+    fn letter_index(&self, letter: u8) -> u8 {
+        let index = letter - b'a';
+        if !self.classic {
+            return index;
+        }
+        match letter {
+            b'a'..=b'i' => index,
+            b'j'..=b'u' => index - 1,
+            _ => index - 2,
+        }
+    }
+
+    fn index_letter(&self, index: u8) -> Option<u8> {
+        let letter = match index {
+            0..=8 if self.classic => index + b'a',
+            9..=19 if self.classic => index + b'a' + 1,
+            20..=23 if self.classic => index + b'a' + 2,
+            0..=25 if !self.classic => index + b'a',
+            _ => return None,
+        };
+        Some(letter)
+    }
+}
+
+impl Codec for BaconCodec {
+    fn name(&self) -> &'static str {
+        "bacon"
+    }
+
+    fn description(&self) -> &'static str {
+        "Bacon's cipher, only letters are encoded, args: classic (24 letters alphabet)"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let letters = data.iter().filter(|b| b.is_ascii_alphabetic());
+        for (i, letter) in letters.enumerate() {
+            if i > 0 {
+                output.push(b' ');
+            }
+            let index = self.letter_index(letter.to_ascii_lowercase());
+            output.extend(
+                (0..5)
+                    .rev()
+                    .map(|bit| if index >> bit & 1 == 0 { b'A' } else { b'B' }),
+            );
+        }
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let bits: Vec<u8> = data
+            .iter()
+            .filter_map(|b| match b {
+                b'A' | b'a' => Some(0),
+                b'B' | b'b' => Some(1),
+                _ => None,
+            })
+            .collect();
+        let groups = bits.chunks_exact(5);
+        if !groups.remainder().is_empty() {
+            return Err(CodecError::Truncated);
+        }
+
+        for group in groups {
+            let index = group.iter().fold(0, |acc, bit| acc << 1 | bit);
+            output.push(self.index_letter(index).ok_or(CodecError::InvalidGroup)?);
+        }
+        Ok(())
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let classic = match args {
+            "" => false,
+            "classic" => true,
+            _ => return None,
+        };
+        Some(Box::new(Self { classic }) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        size / 6
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        size * 6
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let letters = data.iter().filter(|b| !b.is_ascii_whitespace());
+        let count = letters.clone().count();
+        count > 0
+            && count.is_multiple_of(5)
+            && letters
+                .clone()
+                .all(|b| matches!(b, b'A' | b'B' | b'a' | b'b'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &[u8] = b"It was the best of times, it was the worst of times, it was the age of \
+        wisdom, it was the age of foolishness, it was the epoch of belief, it was the epoch of \
+        incredulity, it was the season of Light, it was the season of Darkness, it was the \
+        spring of hope, it was the winter of despair, we had everything before us, we had \
+        nothing before us, we were all going direct to Heaven, we were all going direct the \
+        other way.";
+
+    #[test]
+    fn vigenere() {
+        let codec = VigenereCodec {
+            mode: VigenereMode::Key(parse_key("LEMON").unwrap()),
+        };
+        assert_eq!(
+            codec.encode(b"Attack at dawn!").unwrap(),
+            b"Lxfopv ef rnhr!"
+        );
+
+        let encoded = codec.encode(TEXT).unwrap();
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), TEXT);
+        assert_eq!(
+            recover_key(&encoded[..], DEFAULT_MAX_KEY_LEN),
+            parse_key("LEMON").unwrap()
+        );
+    }
+
+    #[test]
+    fn atbash_affine() {
+        assert_eq!(
+            AtbashCodec.encode(b"Hello, World").unwrap(),
+            b"Svool, Dliow"
+        );
+
+        let affine = AffineCodec::default();
+        let encoded = affine.encode(b"Affine Cipher").unwrap();
+        assert_eq!(encoded, b"Ihhwvc Swfrcp");
+        assert_eq!(affine.decode(&encoded[..]).unwrap(), b"Affine Cipher");
+    }
+
+    #[test]
+    fn rail_fence() {
+        let codec = RailFenceCodec::default();
+        let encoded = codec.encode(b"WEAREDISCOVEREDRUNATONCE").unwrap();
+        assert_eq!(encoded, b"WECRUOERDSOEERNTNEAIVDAC");
+        assert_eq!(
+            codec.decode(&encoded[..]).unwrap(),
+            b"WEAREDISCOVEREDRUNATONCE"
+        );
+        let text = "élève modèle";
+        let encoded = codec.encode(text.as_bytes()).unwrap();
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), text.as_bytes());
+    }
+
+    #[test]
+    fn bacon() {
+        let codec = BaconCodec::default();
+        let encoded = codec.encode(b"Hi Jo").unwrap();
+        assert_eq!(encoded, b"AABBB ABAAA ABAAB ABBBA");
+        assert!(codec.detect(&encoded[..]));
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), b"hijo");
+
+        let classic = BaconCodec { classic: true };
+        let encoded = classic.encode(b"Hi Jo").unwrap();
+        assert_eq!(encoded, b"AABBB ABAAA ABAAA ABBAB");
+        assert_eq!(classic.decode(&encoded[..]).unwrap(), b"hiio");
+    }
+}
@@ -0,0 +1,183 @@
+use std::io::{BufReader, Read};
+
+use crate::codecs::{
+    stream::{ErrorReader, Stream},
+    Codec, Result,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Gzip,
+    Zlib,
+    Deflate,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+impl Format {
+    /// Accepted compression levels and the default one
+    const fn levels(&self) -> (i32, i32, i32) {
+        match self {
+            Self::Gzip | Self::Zlib | Self::Deflate | Self::Xz => (0, 9, 6),
+            Self::Bzip2 => (1, 9, 9),
+            Self::Zstd => (1, 22, zstd::DEFAULT_COMPRESSION_LEVEL),
+        }
+    }
+}
+
+/// Compression formats, decoding decompresses and encoding compresses
+#[derive(Clone)]
+pub struct CompressionCodec {
+    format: Format,
+    level: i32,
+}
+
+impl CompressionCodec {
+    pub const fn new(format: Format) -> Self {
+        let (_, _, level) = format.levels();
+        Self { format, level }
+    }
+}
+
+impl Codec for CompressionCodec {
+    fn name(&self) -> &'static str {
+        match self.format {
+            Format::Gzip => "gzip",
+            Format::Zlib => "zlib",
+            Format::Deflate => "deflate",
+            Format::Bzip2 => "bzip2",
+            Format::Xz => "xz",
+            Format::Zstd => "zstd",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self.format {
+            Format::Gzip => "gzip compression, args: LEVEL (0-9)",
+            Format::Zlib => "zlib compression, args: LEVEL (0-9)",
+            Format::Deflate => "raw deflate compression, args: LEVEL (0-9)",
+            Format::Bzip2 => "bzip2 compression, args: LEVEL (1-9)",
+            Format::Xz => "xz compression, args: LEVEL (0-9)",
+            Format::Zstd => "zstd compression, args: LEVEL (1-22)",
+        }
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        self.encoder(Box::new(data)).read_to_end(output)?;
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        self.decoder(Box::new(data)).read_to_end(output)?;
+        Ok(())
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let (min, max, default) = self.format.levels();
+        let level = if args.is_empty() {
+            default
+        } else {
+            args.parse().ok().filter(|l| (min..=max).contains(l))?
+        };
+        Some(Box::new(Self {
+            format: self.format,
+            level,
+        }) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        size * 4
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        size / 2
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        match self.format {
+            Format::Gzip => data.starts_with(&[0x1f, 0x8b]),
+            Format::Zlib => match data {
+                // Deflate method and header checksum
+                [cmf, flg, ..] => {
+                    cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0
+                }
+                _ => false,
+            },
+            // Raw deflate has no header
+            Format::Deflate => false,
+            Format::Bzip2 => matches!(data, [b'B', b'Z', b'h', b'1'..=b'9', ..]),
+            Format::Xz => data.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]),
+            Format::Zstd => data.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]),
+        }
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        // Levels were checked when building the codec
+        let level = self.level.clamp(0, 9) as u32;
+        match self.format {
+            Format::Gzip => Box::new(flate2::read::GzEncoder::new(
+                reader,
+                flate2::Compression::new(level),
+            )),
+            Format::Zlib => Box::new(flate2::read::ZlibEncoder::new(
+                reader,
+                flate2::Compression::new(level),
+            )),
+            Format::Deflate => Box::new(flate2::read::DeflateEncoder::new(
+                reader,
+                flate2::Compression::new(level),
+            )),
+            Format::Bzip2 => Box::new(bzip2::read::BzEncoder::new(
+                reader,
+                bzip2::Compression::new(level),
+            )),
+            Format::Xz => Box::new(xz2::read::XzEncoder::new(reader, level)),
+            Format::Zstd => match zstd::stream::read::Encoder::new(reader, self.level) {
+                Ok(encoder) => Box::new(encoder),
+                Err(e) => Box::new(ErrorReader::new(e)),
+            },
+        }
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        // Concatenated streams are decoded as a whole, like the command line tools do
+        match self.format {
+            Format::Gzip => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+            Format::Zlib => Box::new(flate2::read::ZlibDecoder::new(reader)),
+            Format::Deflate => Box::new(flate2::read::DeflateDecoder::new(reader)),
+            Format::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(reader)),
+            Format::Xz => Box::new(xz2::read::XzDecoder::new_multi_decoder(reader)),
+            Format::Zstd => {
+                match zstd::stream::read::Decoder::with_buffer(BufReader::new(reader)) {
+                    Ok(decoder) => Box::new(decoder),
+                    Err(e) => Box::new(ErrorReader::new(e)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let data =
+            b"Lorem ipsum dolor sit amet, consectetur adipiscing elit. Lorem ipsum dolor sit amet.";
+        for format in [
+            Format::Gzip,
+            Format::Zlib,
+            Format::Deflate,
+            Format::Bzip2,
+            Format::Xz,
+            Format::Zstd,
+        ] {
+            let codec = CompressionCodec::new(format);
+            let compressed = codec.encode(&data[..]).unwrap();
+            assert_eq!(codec.detect(&compressed[..]), format != Format::Deflate);
+            assert_eq!(codec.decode(&compressed[..]).unwrap(), data);
+        }
+    }
+}
@@ -22,6 +22,51 @@ pub enum CodecError {
 
     /// Cannot find any codec that can decode Input
     NoCodecAvailable,
+
+    /// Input ended in the middle of an encoded sequence
+    Truncated,
+
+    /// Character outside of the codec's alphabet
+    InvalidChar(u8),
+
+    /// Misplaced or missing padding
+    InvalidPadding,
+
+    /// Embedded checksum does not match the data
+    InvalidChecksum,
+
+    /// Encoded group does not fit in its decoded size
+    InvalidGroup,
+
+    /// Input length is not supported by the codec, the number of extra bytes is given
+    InvalidLength(usize),
+
+    /// Expected header was not found
+    MissingHeader,
+
+    /// Expected trailer was not found
+    MissingTrailer,
+
+    /// Input structure is not the expected one
+    Malformed(&'static str),
+
+    /// JSON parse or serialization error
+    Json(serde_json::Error),
+
+    /// Signature does not match the data
+    InvalidSignature,
+
+    /// Algorithm is not implemented
+    UnsupportedAlgorithm(String),
+
+    /// Algorithm is not allowed by the key or the arguments
+    UnexpectedAlgorithm(String),
+
+    /// Operation needs a key
+    MissingKey,
+
+    /// Character cannot be represented in the target charset
+    UnmappableChar(char),
 }
 
 impl fmt::Display for CodecError {
@@ -37,6 +82,35 @@ impl fmt::Display for CodecError {
                 write!(f, "Encounter non-ascii character 0x{:02x}", b)
             }
             CodecError::NoCodecAvailable => f.write_str("Cannot find a suitable codec"),
+            CodecError::Truncated => f.write_str("Input is truncated"),
+            CodecError::InvalidChar(b) => {
+                write!(f, "0x{:02x} is not part of the alphabet", b)
+            }
+            CodecError::InvalidPadding => f.write_str("Invalid padding"),
+            CodecError::InvalidChecksum => f.write_str("Checksum mismatch"),
+            CodecError::InvalidGroup => f.write_str("Encoded group is out of range"),
+            CodecError::InvalidLength(n) => {
+                write!(f, "Input length is off by {} bytes", n)
+            }
+            CodecError::MissingHeader => f.write_str("Missing header"),
+            CodecError::MissingTrailer => f.write_str("Missing trailer"),
+            CodecError::Malformed(what) => write!(f, "Malformed input: {}", what),
+            CodecError::Json(e) => fmt::Display::fmt(e, f),
+            CodecError::InvalidSignature => f.write_str("Invalid signature"),
+            CodecError::UnsupportedAlgorithm(alg) => {
+                write!(f, "Unsupported algorithm {:?}", alg)
+            }
+            CodecError::UnexpectedAlgorithm(alg) => {
+                write!(f, "Algorithm {:?} is not allowed with this key", alg)
+            }
+            CodecError::MissingKey => f.write_str("A key is required"),
+            CodecError::UnmappableChar(c) => {
+                write!(
+                    f,
+                    "U+{:04X} cannot be represented in the charset",
+                    u32::from(*c)
+                )
+            }
         }
     }
 }
@@ -45,7 +119,20 @@ impl std::error::Error for CodecError {}
 
 impl From<io::Error> for CodecError {
     fn from(e: io::Error) -> Self {
-        Self::IO(e)
+        // Codec errors raised while streaming are carried inside I/O errors
+        match e.downcast::<CodecError>() {
+            Ok(e) => e,
+            Err(e) => Self::IO(e),
+        }
+    }
+}
+
+impl From<CodecError> for io::Error {
+    fn from(e: CodecError) -> Self {
+        match e {
+            CodecError::IO(e) => e,
+            e => io::Error::other(e),
+        }
     }
 }
 
@@ -60,3 +147,9 @@ impl From<Utf8Error> for CodecError {
         Self::UTF8(e)
     }
 }
+
+impl From<serde_json::Error> for CodecError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
@@ -0,0 +1,418 @@
+use crate::codecs::{Codec, CodecError, Result};
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    C,
+    Json,
+    Python,
+    JavaScript,
+    Shell,
+}
+
+impl Language {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "c" => Some(Self::C),
+            "json" => Some(Self::Json),
+            "python" | "py" => Some(Self::Python),
+            "javascript" | "js" => Some(Self::JavaScript),
+            "sh" | "shell" | "bash" => Some(Self::Shell),
+            _ => None,
+        }
+    }
+
+    /// Value of single character escapes
+    fn short_escape(&self, c: u8) -> Option<u8> {
+        let value = match c {
+            b'n' => b'\n',
+            b't' => b'\t',
+            b'r' => b'\r',
+            b'b' => 0x08,
+            b'f' => 0x0c,
+            b'\\' | b'"' => c,
+            b'\'' if *self != Self::Json => c,
+            b'/' if *self == Self::Json => c,
+            b'v' if *self != Self::Json => 0x0b,
+            b'a' if matches!(self, Self::C | Self::Python | Self::Shell) => 0x07,
+            b'?' if matches!(self, Self::C | Self::Shell) => c,
+            b'e' | b'E' if *self == Self::Shell => 0x1b,
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Minimal and maximal number of digits of `\x` escapes
+    fn hex_escape_len(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::C | Self::Shell => Some((1, 2)),
+            Self::Python | Self::JavaScript => Some((2, 2)),
+            Self::Json => None,
+        }
+    }
+
+    fn has_octal_escapes(&self) -> bool {
+        matches!(self, Self::C | Self::Python | Self::Shell)
+    }
+
+    fn has_long_unicode_escapes(&self) -> bool {
+        matches!(self, Self::C | Self::Python | Self::Shell)
+    }
+}
+
+fn get_hex_value(b: u8) -> Option<u32> {
+    char::from(b).to_digit(16)
+}
+
+/// Parses between `min` and `max` digits in `radix`, returns the value and the number of digits
+fn parse_number(data: &[u8], radix: u32, min: usize, max: usize) -> Result<(u32, usize)> {
+    let len = data
+        .iter()
+        .take(max)
+        .take_while(|b| char::from(**b).is_digit(radix))
+        .count();
+    if len < min {
+        return Err(match data.get(len) {
+            Some(b) => CodecError::InvalidHexDigit(*b),
+            None => CodecError::Truncated,
+        });
+    }
+    let value = data[..len].iter().fold(0, |acc, b| {
+        acc * radix + char::from(*b).to_digit(radix).unwrap()
+    });
+    Ok((value, len))
+}
+
+/// Byte written by a `\x` or octal escape, larger values are not truncated
+fn escaped_byte(value: u32) -> Result<u8> {
+    u8::try_from(value).map_err(|_| CodecError::Malformed("Escaped value does not fit in a byte"))
+}
+
+fn push_char(code_point: u32, output: &mut Vec<u8>) {
+    let c = char::from_u32(code_point).unwrap_or(char::REPLACEMENT_CHARACTER);
+    output.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+/// Removes the quotes around a whole string literal
+fn strip_quotes(data: &[u8]) -> &[u8] {
+    let trimmed = data.trim_ascii();
+    let trimmed = trimmed.strip_prefix(b"$").unwrap_or(trimmed);
+    match trimmed {
+        [b'"', inner @ .., b'"'] | [b'\'', inner @ .., b'\''] => inner,
+        _ => data,
+    }
+}
+
+/// String literal escapes of programming languages
+#[derive(Clone)]
+pub struct EscapeCodec {
+    language: Language,
+}
+
+impl Default for EscapeCodec {
+    fn default() -> Self {
+        Self {
+            language: Language::C,
+        }
+    }
+}
+
+impl EscapeCodec {
+    /// Parses the escape sequence in `data`, starting after the backslash. Returns the number of
+    /// bytes read.
+    fn unescape_one(&self, data: &[u8], output: &mut Vec<u8>) -> Result<usize> {
+        let language = self.language;
+        let Some(&c) = data.first() else {
+            return Err(CodecError::Truncated);
+        };
+        let rest = &data[1..];
+
+        if let Some(value) = language.short_escape(c) {
+            output.push(value);
+            return Ok(1);
+        }
+
+        match c {
+            b'x' => {
+                if let Some((min, max)) = language.hex_escape_len() {
+                    // Always a byte, so shellcode goes through unchanged. Unlike C compilers, at
+                    // most two digits are read, the next ones are plain characters.
+                    let (value, len) = parse_number(rest, 16, min, max)?;
+                    output.push(escaped_byte(value)?);
+                    return Ok(1 + len);
+                }
+            }
+            b'0'..=b'7' if language.has_octal_escapes() => {
+                let (value, len) = parse_number(data, 8, 1, 3)?;
+                output.push(escaped_byte(value)?);
+                return Ok(len);
+            }
+            b'0' if language == Language::JavaScript => {
+                output.push(0);
+                return Ok(1);
+            }
+            b'u' if language == Language::JavaScript && rest.first() == Some(&b'{') => {
+                let (value, len) = parse_number(&rest[1..], 16, 1, 6)?;
+                if rest.get(1 + len) != Some(&b'}') {
+                    return Err(CodecError::MissingTrailer);
+                }
+                push_char(value, output);
+                return Ok(3 + len);
+            }
+            b'u' => {
+                let (mut value, mut len) = parse_number(rest, 16, 4, 4)?;
+                // UTF-16 surrogate pair
+                if (0xd800..0xdc00).contains(&value)
+                    && let Some(low) = rest[4..].strip_prefix(b"\\u")
+                    && let Ok((low, 4)) = parse_number(low, 16, 4, 4)
+                    && (0xdc00..0xe000).contains(&low)
+                {
+                    value = 0x10000 + ((value - 0xd800) << 10) + (low - 0xdc00);
+                    len += 6;
+                }
+                push_char(value, output);
+                return Ok(1 + len);
+            }
+            b'U' if language.has_long_unicode_escapes() => {
+                let (value, len) = parse_number(rest, 16, 8, 8)?;
+                push_char(value, output);
+                return Ok(1 + len);
+            }
+            b'c' if language == Language::Shell => {
+                let Some(control) = rest.first() else {
+                    return Err(CodecError::Truncated);
+                };
+                output.push(control & 0x1f);
+                return Ok(2);
+            }
+            // Line continuation
+            b'\n' if matches!(language, Language::Python | Language::JavaScript) => {
+                return Ok(1);
+            }
+            _ => {}
+        }
+
+        match language {
+            Language::Json => Err(CodecError::InvalidChar(c)),
+            // Unknown escapes are the character itself
+            Language::JavaScript => {
+                output.push(c);
+                Ok(1)
+            }
+            _ => {
+                output.extend_from_slice(&[b'\\', c]);
+                Ok(1)
+            }
+        }
+    }
+
+    fn push_hex_escape(b: u8, output: &mut Vec<u8>) {
+        output.extend_from_slice(&[
+            b'\\',
+            b'x',
+            HEX_DIGITS[usize::from(b >> 4)],
+            HEX_DIGITS[usize::from(b & 0xf)],
+        ]);
+    }
+
+    fn push_unicode_escape(&self, c: char, output: &mut Vec<u8>) {
+        let code_point = c as u32;
+        let escaped = match self.language {
+            _ if code_point <= 0xffff => format!("\\u{code_point:04x}"),
+            Language::JavaScript => format!("\\u{{{code_point:x}}}"),
+            Language::Python => format!("\\U{code_point:08x}"),
+            _ => {
+                let mut units = [0; 2];
+                c.encode_utf16(&mut units)
+                    .iter()
+                    .map(|unit| format!("\\u{unit:04x}"))
+                    .collect()
+            }
+        };
+        output.extend_from_slice(escaped.as_bytes());
+    }
+}
+
+impl Codec for EscapeCodec {
+    fn name(&self) -> &'static str {
+        "escape"
+    }
+
+    fn description(&self) -> &'static str {
+        "String literal escapes, args: c (default), json, python, js or sh"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let language = self.language;
+        // Code points are escaped for languages with Unicode strings, bytes otherwise
+        let text = match language {
+            Language::Json => Some(std::str::from_utf8(data)?),
+            Language::Python | Language::JavaScript => std::str::from_utf8(data).ok(),
+            Language::C | Language::Shell => None,
+        };
+
+        let mut i = 0;
+        let mut hex_escaped = false;
+        while i < data.len() {
+            let b = data[i];
+            let escaped = match b {
+                b'\n' => Some(b'n'),
+                b'\t' => Some(b't'),
+                b'\r' => Some(b'r'),
+                b'\\' | b'"' => Some(b),
+                b'\'' if language != Language::Json => Some(b),
+                _ => None,
+            };
+
+            let printable = b.is_ascii_graphic() || b == b' ';
+            match escaped {
+                Some(e) => output.extend_from_slice(&[b'\\', e]),
+                // C reads as many hex digits as there are after `\x`
+                None if printable
+                    && !(language == Language::C && hex_escaped && b.is_ascii_hexdigit()) =>
+                {
+                    output.push(b)
+                }
+                None if b.is_ascii() && language == Language::Json => {
+                    self.push_unicode_escape(char::from(b), output);
+                }
+                None if !b.is_ascii() && text.is_some() => {
+                    let c = text.unwrap()[i..].chars().next().unwrap();
+                    self.push_unicode_escape(c, output);
+                    i += c.len_utf8();
+                    hex_escaped = false;
+                    continue;
+                }
+                None => {
+                    Self::push_hex_escape(b, output);
+                    hex_escaped = true;
+                    i += 1;
+                    continue;
+                }
+            }
+            hex_escaped = false;
+            i += 1;
+        }
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let data = strip_quotes(data);
+        let mut i = 0;
+        while i < data.len() {
+            if data[i] == b'\\' {
+                i += 1 + self.unescape_one(&data[i + 1..], output)?;
+            } else {
+                output.push(data[i]);
+                i += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let language = if args.is_empty() {
+            Language::C
+        } else {
+            Language::parse(args)?
+        };
+        Some(Box::new(Self { language }) as super::Plugin)
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        size * 2
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        // Numeric escapes, short ones are too common in regular text
+        data.windows(4).any(|w| match w {
+            [b'\\', b'x', high, low] => {
+                get_hex_value(*high).is_some() && get_hex_value(*low).is_some()
+            }
+            [b'\\', b'u', digits @ ..] => digits.iter().all(u8::is_ascii_hexdigit),
+            [b'\\', digits @ ..] => digits.iter().all(|b| (b'0'..=b'7').contains(b)),
+            _ => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codec(language: Language) -> EscapeCodec {
+        EscapeCodec { language }
+    }
+
+    #[test]
+    fn unescape() {
+        assert_eq!(
+            codec(Language::C)
+                .decode(br#""\x31\xc0\x50\150\n\e""#)
+                .unwrap(),
+            b"\x31\xc0\x50h\n\\e"
+        );
+        assert_eq!(
+            codec(Language::Json)
+                .decode(br#"caf\u00e9 \ud83d\ude00 \/"#)
+                .unwrap(),
+            "café 😀 /".as_bytes()
+        );
+        assert!(codec(Language::Json).decode(br"\x41").is_err());
+        assert_eq!(
+            codec(Language::JavaScript)
+                .decode(br"\u{1f600}\q\0")
+                .unwrap(),
+            "😀q\0".as_bytes()
+        );
+        assert_eq!(
+            codec(Language::Shell).decode(br"$'\e[1m\cA\x7'").unwrap(),
+            b"\x1b[1m\x01\x07"
+        );
+        assert_eq!(
+            codec(Language::Python).decode(br"\U0001f600\101").unwrap(),
+            "😀A".as_bytes()
+        );
+    }
+
+    #[test]
+    fn escape_range() {
+        let c = codec(Language::C);
+        assert_eq!(c.decode(br"\377\x414").unwrap(), b"\xffA4");
+        for escape in [&br"\400"[..], br"\777"] {
+            assert!(matches!(c.decode(escape), Err(CodecError::Malformed(_))));
+        }
+        assert!(codec(Language::Python).decode(br"\400").is_err());
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(
+            codec(Language::C).encode(b"\x90\x90AB\n\x01f").unwrap(),
+            br#"\x90\x90\x41\x42\n\x01\x66"#
+        );
+        assert_eq!(
+            codec(Language::Json)
+                .encode("é😀\"\x01".as_bytes())
+                .unwrap(),
+            br#"\u00e9\ud83d\ude00\"\u0001"#
+        );
+        assert_eq!(
+            codec(Language::JavaScript)
+                .encode("é😀".as_bytes())
+                .unwrap(),
+            br"\u00e9\u{1f600}"
+        );
+        assert_eq!(
+            codec(Language::Python)
+                .encode("😀\u{ff}".as_bytes())
+                .unwrap(),
+            br"\U0001f600\u00ff"
+        );
+        assert_eq!(
+            codec(Language::Python).encode(b"\xff'").unwrap(),
+            br"\xff\'"
+        );
+    }
+}
@@ -1,9 +1,35 @@
-use std::fmt::write;
+use crate::codecs::{
+    stream::{transform_all, BufferedReader, Transform, TransformReader},
+    Codec, CodecError, Result, Stream,
+};
 
-use crate::codecs::{Codec, CodecError, Result};
+/// Bytes looked at to find the declaration of an array
+const DECLARATION_MAX_LEN: usize = 256;
 
-#[derive(Default)]
-pub struct HexCodec;
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    #[default]
+    Plain,
+    C,
+    Python,
+    Rust,
+}
+
+#[derive(Default, Clone)]
+pub struct HexCodec {
+    upper: bool,
+
+    /// Written between bytes on the same line
+    separator: &'static str,
+
+    /// Written before each byte
+    prefix: &'static str,
+
+    /// Bytes per line, everything is on a single line when zero
+    width: usize,
+
+    format: Format,
+}
 
 fn get_hex_value(high: u8, low: u8) -> Result<u8> {
     if !high.is_ascii_hexdigit() {
@@ -19,42 +45,214 @@ fn get_hex_value(high: u8, low: u8) -> Result<u8> {
     Ok(u8::from_str_radix(s, 16).unwrap())
 }
 
+impl HexCodec {
+    /// Text before the first byte and after the last one
+    fn enclosing(&self, len: usize) -> (String, &'static str) {
+        match self.format {
+            Format::Plain => (String::new(), ""),
+            Format::C => ("unsigned char data[] = {\n  ".to_owned(), "\n};\n"),
+            Format::Python => ("data = (\n    b\"".to_owned(), "\"\n)\n"),
+            Format::Rust => (format!("const DATA: [u8; {len}] = [\n    "), "\n];\n"),
+        }
+    }
+
+    /// Text between the last byte of a line and the first one of the next line
+    fn line_break(&self) -> &'static str {
+        match self.format {
+            Format::Plain => "\n",
+            Format::C => ",\n  ",
+            Format::Python => "\"\n    b\"",
+            Format::Rust => ",\n    ",
+        }
+    }
+
+    fn new_encoder(&self) -> HexEncoder<'_> {
+        HexEncoder {
+            codec: self,
+            count: 0,
+        }
+    }
+}
+
+struct HexEncoder<'a> {
+    codec: &'a HexCodec,
+
+    /// Number of bytes encoded so far
+    count: usize,
+}
+
+impl Transform for HexEncoder<'_> {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        let codec = self.codec;
+        if self.count == 0 && (!input.is_empty() || eof) {
+            output.extend_from_slice(codec.enclosing(input.len()).0.as_bytes());
+        }
+
+        for b in input {
+            if self.count > 0 {
+                if codec.width > 0 && self.count.is_multiple_of(codec.width) {
+                    output.extend_from_slice(codec.line_break().as_bytes());
+                } else {
+                    output.extend_from_slice(codec.separator.as_bytes());
+                }
+            }
+            let hex = if codec.upper {
+                format!("{}{b:02X}", codec.prefix)
+            } else {
+                format!("{}{b:02x}", codec.prefix)
+            };
+            output.extend_from_slice(hex.as_bytes());
+            self.count += 1;
+        }
+
+        if eof {
+            output.extend_from_slice(codec.enclosing(self.count).1.as_bytes());
+        }
+        Ok(input.len())
+    }
+}
+
+/// Streaming decoder accepting separators, `0x` or `\x` prefixes and array literals. Unlike
+/// [`HexCodec::decode_into`] it cannot know the total length upfront so an odd number of digits
+/// is an error.
+#[derive(Default)]
+struct HexDecoder {
+    /// The declaration before an array literal was looked for
+    started: bool,
+
+    /// Digits read in the current group
+    group_len: usize,
+
+    /// The array literal is closed, what follows is ignored
+    done: bool,
+}
+
+impl HexDecoder {
+    /// Skips up to the `=` of a declaration like `unsigned char data[] = {`
+    fn skip_declaration(&mut self, input: &[u8], eof: bool) -> Option<usize> {
+        let line_len = input.iter().position(|b| *b == b'\n');
+        if line_len.is_none() && !eof && input.len() < DECLARATION_MAX_LEN {
+            return None;
+        }
+
+        self.started = true;
+        let line = &input[..line_len.unwrap_or(input.len()).min(DECLARATION_MAX_LEN)];
+        Some(
+            line.iter()
+                .position(|b| *b == b'=')
+                .map_or(0, |pos| pos + 1),
+        )
+    }
+}
+
+impl Transform for HexDecoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        let mut i = 0;
+        if !self.started {
+            match self.skip_declaration(input, eof) {
+                Some(start) => i = start,
+                None => return Ok(0),
+            }
+        }
+
+        while i < input.len() && !self.done {
+            let b = input[i];
+            let next = input.get(i + 1).copied();
+            // Wait for the next byte, it may complete a prefix or a pair
+            if next.is_none() && !eof && (b.is_ascii_hexdigit() || b == b'\\') {
+                return Ok(i);
+            }
+
+            match (b, next) {
+                (b'0' | b'\\', Some(b'x' | b'X')) => {
+                    self.group_len = 0;
+                    i += 2;
+                }
+                // Python bytes literal
+                (b'b' | b'B', Some(b'"' | b'\'')) => {
+                    self.group_len = 0;
+                    i += 2;
+                }
+                (_, Some(next)) if b.is_ascii_hexdigit() && next.is_ascii_hexdigit() => {
+                    output.push(get_hex_value(b, next)?);
+                    self.group_len += 2;
+                    i += 2;
+                }
+                // A single digit, like `0x5` in an array
+                _ if b.is_ascii_hexdigit() && self.group_len == 0 => {
+                    output.push(get_hex_value(b'0', b)?);
+                    i += 1;
+                }
+                _ if b.is_ascii_hexdigit() => {
+                    return Err(next.map_or(CodecError::Truncated, CodecError::InvalidHexDigit));
+                }
+                (b'}' | b']' | b')' | b';', _) => self.done = true,
+                (b':' | b',' | b'-' | b'"' | b'\'' | b'{' | b'[' | b'(' | b'\\', _) => {
+                    self.group_len = 0;
+                    i += 1;
+                }
+                _ if b.is_ascii_whitespace() => {
+                    self.group_len = 0;
+                    i += 1;
+                }
+                _ => return Err(CodecError::InvalidHexDigit(b)),
+            }
+        }
+        Ok(input.len())
+    }
+}
+
 impl Codec for HexCodec {
     fn name(&self) -> &'static str {
         "hex"
     }
 
     fn description(&self) -> &'static str {
-        "lowercase hexadecimal"
+        "hexadecimal, args: upper, colon, space, comma, \\x, 0x, width=N, c, python or rust"
     }
 
     fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-        let mut s = String::new();
-        for b in data {
-            s.clear();
-            write(&mut s, format_args!("{:02x}", b)).unwrap();
-            output.extend_from_slice(s.as_bytes());
-        }
+        self.new_encoder().transform(data, output, true)?;
         Ok(())
     }
 
     fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-        let data = if data.len() & 1 == 1 {
-            output.push(get_hex_value(0, data[0])?);
-            &data[1..]
-        } else {
-            data
-        };
-        for i in 0..(data.len() / 2) {
-            let b = get_hex_value(data[2 * i], data[2 * i + 1])?;
-            output.push(b);
-        }
-        Ok(())
+        transform_all(HexDecoder::default(), data, output)
     }
 
     fn build(&self, args: &str) -> Option<super::Plugin> {
-        let _ = args;
-        Some(Box::new(Self) as super::Plugin)
+        let mut codec = Self::default();
+        for arg in args.split(',').filter(|a| !a.is_empty()) {
+            match arg.split_once('=') {
+                None => match arg {
+                    "upper" => codec.upper = true,
+                    "colon" => codec.separator = ":",
+                    "space" => codec.separator = " ",
+                    "comma" => codec.separator = ", ",
+                    "\\x" => codec.prefix = "\\x",
+                    "0x" => codec.prefix = "0x",
+                    "c" => codec.format = Format::C,
+                    "python" | "py" => codec.format = Format::Python,
+                    "rust" => codec.format = Format::Rust,
+                    _ => return None,
+                },
+                Some(("width", n)) => codec.width = n.parse().ok()?,
+                _ => return None,
+            }
+        }
+
+        // Arrays have their own syntax, and are wrapped like `xxd -i` does by default
+        let (prefix, separator, width) = match codec.format {
+            Format::Plain => (codec.prefix, codec.separator, 0),
+            Format::C | Format::Rust => ("0x", ", ", 12),
+            Format::Python => ("\\x", "", 16),
+        };
+        codec.prefix = prefix;
+        codec.separator = separator;
+        if codec.width == 0 {
+            codec.width = width;
+        }
+        Some(Box::new(codec) as super::Plugin)
     }
 
     fn decoded_size_hint(&self, size: usize) -> usize {
@@ -62,6 +260,85 @@ impl Codec for HexCodec {
     }
 
     fn encoded_size_hint(&self, size: usize) -> usize {
-        size * 2
+        size * (2 + self.prefix.len() + self.separator.len())
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        // Separators and prefixes are accepted, but digits should still make most of the input
+        let digits = data.iter().filter(|b| b.is_ascii_hexdigit()).count();
+        digits >= 2 && digits * 2 >= data.len()
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        // The length of Rust arrays is part of their type
+        if self.format == Format::Rust {
+            return Box::new(BufferedReader::new(reader, move |data| self.encode(data)));
+        }
+        Box::new(TransformReader::new(reader, self.new_encoder()))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, HexDecoder::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    fn codec(args: &str) -> crate::codecs::Plugin {
+        HexCodec::default().build(args).unwrap()
+    }
+
+    #[test]
+    fn formats() {
+        let data = b"Hi\n\xff";
+        for (args, encoded) in [
+            ("", "48690aff"),
+            ("upper,colon", "48:69:0A:FF"),
+            ("space,width=2", "48 69\n0a ff"),
+            ("0x,comma", "0x48, 0x69, 0x0a, 0xff"),
+            ("\\x", "\\x48\\x69\\x0a\\xff"),
+            (
+                "c",
+                "unsigned char data[] = {\n  0x48, 0x69, 0x0a, 0xff\n};\n",
+            ),
+            (
+                "python,width=2",
+                "data = (\n    b\"\\x48\\x69\"\n    b\"\\x0a\\xff\"\n)\n",
+            ),
+            (
+                "rust,width=3",
+                "const DATA: [u8; 4] = [\n    0x48, 0x69, 0x0a,\n    0xff\n];\n",
+            ),
+        ] {
+            let codec = codec(args);
+            assert_eq!(codec.encode(data).unwrap(), encoded.as_bytes(), "{args}");
+            assert_eq!(codec.decode(encoded.as_bytes()).unwrap(), data, "{args}");
+        }
+    }
+
+    #[test]
+    fn lenient_decode() {
+        let codec = HexCodec::default();
+        assert!(matches!(codec.decode(b"abc"), Err(CodecError::Truncated)));
+        let mut decoded = Vec::new();
+        assert!(codec
+            .decoder(Box::new(&b"abc"[..]))
+            .read_to_end(&mut decoded)
+            .is_err());
+        assert_eq!(codec.decode(b"{0x5, 0x10}").unwrap(), b"\x05\x10");
+        assert_eq!(
+            codec
+                .decode(b"unsigned char hi[] = {\n  0x48, 0x69\n};\nunsigned int hi_len = 2;\n")
+                .unwrap(),
+            b"Hi"
+        );
+        assert_eq!(codec.decode(b"de-ad be:ef").unwrap(), b"\xde\xad\xbe\xef");
+        assert!(codec.decode(b"48 6").is_ok());
+        assert!(codec.decode(b"486 9").is_err());
+        assert!(codec.decode(b"hello").is_err());
     }
 }
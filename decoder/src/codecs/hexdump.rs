@@ -0,0 +1,434 @@
+use std::fmt::Write;
+
+use crate::codecs::{
+    stream::{transform_all, Transform, TransformReader},
+    Codec, CodecError, Result, Stream,
+};
+
+const BYTES_PER_LINE: usize = 16;
+
+/// Most bytes of repeated lines written per call, long runs of `*` lines are streamed
+const REPEAT_CHUNK_LEN: usize = 64 * 1024;
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    /// `xxd`
+    #[default]
+    Xxd,
+
+    /// `hexdump -C`
+    Canonical,
+}
+
+fn ascii_column(line: &[u8]) -> String {
+    line.iter()
+        .map(|b| {
+            if b.is_ascii_graphic() || *b == b' ' {
+                char::from(*b)
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+struct HexdumpEncoder {
+    style: Style,
+    offset: usize,
+}
+
+impl HexdumpEncoder {
+    fn write_line(&mut self, line: &[u8], output: &mut Vec<u8>) {
+        let mut hex = String::new();
+        let text = match self.style {
+            Style::Xxd => {
+                for (i, b) in line.iter().enumerate() {
+                    if i > 0 && i % 2 == 0 {
+                        hex.push(' ');
+                    }
+                    write!(hex, "{b:02x}").unwrap();
+                }
+                let width = BYTES_PER_LINE * 5 / 2 - 1;
+                format!(
+                    "{:08x}: {hex:width$}  {}\n",
+                    self.offset,
+                    ascii_column(line)
+                )
+            }
+            Style::Canonical => {
+                for (i, b) in line.iter().enumerate() {
+                    if i == BYTES_PER_LINE / 2 {
+                        hex.push(' ');
+                    }
+                    write!(hex, "{b:02x} ").unwrap();
+                }
+                let width = BYTES_PER_LINE * 3 + 1;
+                format!(
+                    "{:08x}  {hex:width$} |{}|\n",
+                    self.offset,
+                    ascii_column(line)
+                )
+            }
+        };
+        output.extend_from_slice(text.as_bytes());
+        self.offset += line.len();
+    }
+}
+
+impl Transform for HexdumpEncoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        let mut lines = input.chunks_exact(BYTES_PER_LINE);
+        for line in lines.by_ref() {
+            self.write_line(line, output);
+        }
+
+        let rest = lines.remainder();
+        if !eof {
+            return Ok(input.len() - rest.len());
+        }
+        if !rest.is_empty() {
+            self.write_line(rest, output);
+        }
+        // hexdump ends with the total length
+        if self.style == Style::Canonical {
+            output.extend_from_slice(format!("{:08x}\n", self.offset).as_bytes());
+        }
+        Ok(input.len())
+    }
+}
+
+/// Line of a dump, the offset is kept as text since its radix is not known
+struct DumpLine<'a> {
+    offset: Option<&'a str>,
+    bytes: Vec<u8>,
+}
+
+fn is_hex(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Parses a line of `xxd`, `hexdump`, `od -x` or Wireshark output, returns `None` if it does not
+/// look like one.
+fn parse_line(line: &str) -> Option<DumpLine<'_>> {
+    let line = line.trim_end();
+    let trimmed = line.trim_start();
+    let (first, after_first) = trimmed.split_at(trimmed.find(' ').unwrap_or(trimmed.len()));
+    let next = after_first.split_whitespace().next();
+
+    let (offset, columns, colon) = if let Some(offset) = first.strip_suffix(':') {
+        (Some(offset), after_first, true)
+    } else if is_hex(first) && next.is_none_or(|n| n.len() != first.len()) {
+        (Some(first), after_first, false)
+    } else {
+        (None, trimmed, false)
+    };
+    if offset.is_some_and(|o| !is_hex(o)) {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    let mut group_len = None;
+    let mut rest = columns;
+    loop {
+        let token_start = rest.trim_start_matches(' ');
+        let gap = rest.len() - token_start.len();
+        // The ASCII column is at least two spaces away, like both halves of `hexdump -C` lines
+        let halves = gap == 2 && bytes.len() == BYTES_PER_LINE / 2;
+        if token_start.is_empty() || (!bytes.is_empty() && gap >= 2 && !halves) {
+            break;
+        }
+
+        let (token, after) =
+            token_start.split_at(token_start.find(' ').unwrap_or(token_start.len()));
+        if !is_hex(token) || token.len() % 2 != 0 || group_len.is_some_and(|l| l != token.len()) {
+            break;
+        }
+        group_len = Some(token.len());
+        for pair in token.as_bytes().chunks_exact(2) {
+            let pair = std::str::from_utf8(pair).unwrap();
+            bytes.push(u8::from_str_radix(pair, 16).unwrap());
+        }
+        rest = after;
+    }
+
+    if offset.is_none() && bytes.is_empty() {
+        return None;
+    }
+    // `od -x` and `hexdump` show little endian words
+    if offset.is_some() && !colon && group_len == Some(4) {
+        for word in bytes.chunks_exact_mut(2) {
+            word.swap(0, 1);
+        }
+    }
+    Some(DumpLine { offset, bytes })
+}
+
+#[derive(Default)]
+struct HexdumpDecoder {
+    /// Radix of offsets, found by comparing them with the number of bytes decoded
+    offset_radix: Option<u32>,
+
+    /// Number of bytes decoded so far, including `last_line`
+    position: usize,
+
+    /// Bytes of the last line, held back as a final offset may truncate them
+    last_line: Vec<u8>,
+
+    /// A `*` line stands for repetitions of the last line up to the next offset
+    repeat: bool,
+}
+
+impl HexdumpDecoder {
+    fn parse_offset(&mut self, offset: &str) -> Result<usize> {
+        let parse = |radix| usize::from_str_radix(offset, radix).ok();
+        if self.offset_radix.is_none() && !self.repeat && self.position > 0 {
+            if parse(16) == Some(self.position) {
+                self.offset_radix = Some(16);
+            } else if parse(8) == Some(self.position) {
+                self.offset_radix = Some(8);
+            }
+        }
+        // Until then, seven digits are the default of `od`
+        let radix = self.offset_radix.unwrap_or_else(|| {
+            if offset.len() == 7 && parse(8).is_some() {
+                8
+            } else {
+                16
+            }
+        });
+        parse(radix).ok_or(CodecError::Malformed("Invalid offset"))
+    }
+
+    fn push_line(&mut self, bytes: Vec<u8>, output: &mut Vec<u8>) {
+        output.append(&mut self.last_line);
+        self.position += bytes.len();
+        self.last_line = bytes;
+    }
+
+    /// Decodes a line, returns `false` when repeated lines reach `limit` bytes of output: the
+    /// same line must then be given again to write the rest.
+    fn decode_line(&mut self, line: &str, output: &mut Vec<u8>, limit: usize) -> Result<bool> {
+        if line.trim() == "*" {
+            self.repeat = true;
+            return Ok(true);
+        }
+        let Some(line) = parse_line(line) else {
+            return Ok(true);
+        };
+
+        if let Some(offset) = line.offset {
+            let offset = self.parse_offset(offset)?;
+            if self.repeat {
+                let repeated = self.last_line.clone();
+                if repeated.is_empty() {
+                    return Err(CodecError::Malformed("Nothing to repeat"));
+                }
+                while self.position < offset {
+                    if output.len() >= limit {
+                        return Ok(false);
+                    }
+                    self.push_line(repeated.clone(), output);
+                }
+                self.repeat = false;
+            }
+            // The final offset gives the length, dumps of words pad the last line
+            let extra = self.position.saturating_sub(offset);
+            if line.bytes.is_empty() && extra < self.last_line.len() {
+                self.last_line.truncate(self.last_line.len() - extra);
+                self.position = offset;
+            }
+        }
+
+        if !line.bytes.is_empty() {
+            self.push_line(line.bytes, output);
+        }
+        Ok(true)
+    }
+}
+
+impl Transform for HexdumpDecoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        let end = match input.iter().rposition(|b| *b == b'\n') {
+            _ if eof => input.len(),
+            Some(pos) => pos + 1,
+            None => return Ok(0),
+        };
+
+        let limit = output.len() + REPEAT_CHUNK_LEN;
+        let mut pos = 0;
+        while pos < end {
+            let line_end = input[pos..end]
+                .iter()
+                .position(|b| *b == b'\n')
+                .map_or(end, |i| pos + i);
+            let line = String::from_utf8_lossy(&input[pos..line_end]);
+            if !self.decode_line(&line, output, limit)? {
+                return Ok(pos);
+            }
+            pos = line_end + 1;
+        }
+        if eof {
+            output.append(&mut self.last_line);
+        }
+        Ok(end)
+    }
+}
+
+/// Hex dumps with offsets and an ASCII column
+#[derive(Default, Clone)]
+pub struct HexdumpCodec {
+    style: Style,
+}
+
+impl Codec for HexdumpCodec {
+    fn name(&self) -> &'static str {
+        "hexdump"
+    }
+
+    fn description(&self) -> &'static str {
+        "Hex dump, decodes xxd, hexdump, od -x and Wireshark output, args: xxd (default) or C for \
+         hexdump -C output"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(
+            HexdumpEncoder {
+                style: self.style,
+                offset: 0,
+            },
+            data,
+            output,
+        )
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(HexdumpDecoder::default(), data, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let style = match args {
+            "" | "xxd" => Style::Xxd,
+            "C" | "canonical" => Style::Canonical,
+            _ => return None,
+        };
+        Some(Box::new(Self { style }) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        size / 4
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        size * 5
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let text = String::from_utf8_lossy(data);
+        let Some(line) = text.lines().find(|l| !l.trim().is_empty()) else {
+            return false;
+        };
+        parse_line(line).is_some_and(|l| l.offset.is_some() && !l.bytes.is_empty())
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(
+            reader,
+            HexdumpEncoder {
+                style: self.style,
+                offset: 0,
+            },
+        ))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, HexdumpDecoder::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    #[test]
+    fn xxd() {
+        let dump = "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a 6162  Hello, world!.ab\n\
+                    00000010: 63                                       c\n";
+        let codec = HexdumpCodec::default();
+        assert_eq!(
+            codec.encode(b"Hello, world!\nabc").unwrap(),
+            dump.as_bytes()
+        );
+        assert_eq!(
+            codec.decode(dump.as_bytes()).unwrap(),
+            b"Hello, world!\nabc"
+        );
+        assert!(codec.detect(dump.as_bytes()));
+        assert!(!codec.detect(b"Hello, world!"));
+    }
+
+    #[test]
+    fn canonical() {
+        let codec = HexdumpCodec {
+            style: Style::Canonical,
+        };
+        assert_eq!(
+            codec.encode(b"Hello, world!\n").unwrap(),
+            b"00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a        |Hello, world!.|\n\
+              0000000e\n"
+        );
+
+        let dump =
+            b"00000000  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n\
+                     *\n\
+                     00000020  00 00 00 00 00 00 00 00  61 62                    |........ab|\n\
+                     0000002a\n";
+        let mut expected = vec![0; 40];
+        expected.extend_from_slice(b"ab");
+        assert_eq!(codec.decode(dump).unwrap(), expected);
+    }
+
+    #[test]
+    fn long_repeat_is_streamed() {
+        let len = 4 << 20;
+        let dump = format!("00000000: 0000 0000\n*\n{len:08x}: 6869\n");
+        let mut decoder = HexdumpDecoder::default();
+        let mut output = Vec::new();
+        let consumed = decoder
+            .transform(dump.as_bytes(), &mut output, true)
+            .unwrap();
+        assert!(consumed < dump.len());
+        assert!(output.len() <= REPEAT_CHUNK_LEN + 4);
+
+        let codec = HexdumpCodec::default();
+        let mut decoded = Vec::new();
+        codec
+            .decoder(Box::new(dump.as_bytes()))
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded.len(), len + 2);
+        assert!(decoded[..len].iter().all(|b| *b == 0));
+        assert_eq!(&decoded[len..], b"hi");
+        assert_eq!(codec.decode(dump.as_bytes()).unwrap(), decoded);
+    }
+
+    #[test]
+    fn od_and_wireshark() {
+        let codec = HexdumpCodec::default();
+        let od = b"0000000 6548 6c6c 2c6f 7720 726f 646c 0a21 6261\n0000020 0063\n0000021\n";
+        assert_eq!(codec.decode(od).unwrap(), b"Hello, world!\nabc");
+        let od = b"0000000 0000 0000 0000 0000 0000 0000 0000 0000\n*\n0000040 6968\n0000042\n";
+        let mut expected = vec![0; 32];
+        expected.extend_from_slice(b"hi");
+        assert_eq!(codec.decode(od).unwrap(), expected);
+
+        let wireshark =
+            b"0000  45 00 00 3c 1c 46 40 00  40 06 b1 e6 c0 a8 00 68   E..<.F@.@......h\n\
+                          0010  c0 a8 00 01 de ad                                 ......\n";
+        assert_eq!(
+            codec.decode(wireshark).unwrap(),
+            b"\x45\x00\x00\x3c\x1c\x46\x40\x00\x40\x06\xb1\xe6\xc0\xa8\x00\x68\xc0\xa8\x00\x01\xde\xad"
+        );
+    }
+}
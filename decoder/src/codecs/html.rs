@@ -0,0 +1,275 @@
+use std::fmt::Write;
+
+use crate::codecs::{
+    stream::{transform_all, Transform, TransformReader},
+    Codec, Result, Stream,
+};
+
+mod entities;
+
+use entities::ENTITIES;
+
+/// Longest entity name is `&CounterClockwiseContourIntegral;`
+const MAX_ENTITY_LEN: usize = 40;
+
+/// Numeric references in the C1 range are read as windows-1252, like browsers do
+const WINDOWS_1252: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+fn get_entity(name: &[u8]) -> Option<&'static str> {
+    ENTITIES
+        .binary_search_by(|(n, _)| n.as_bytes().cmp(name))
+        .ok()
+        .map(|idx| ENTITIES[idx].1)
+}
+
+/// Parses `&#NNN;` or `&#xHHH;` with `data` starting after `&#`, returns the character and the
+/// number of bytes read
+fn parse_numeric(data: &[u8]) -> Option<(char, usize)> {
+    let (radix, start) = match data.first() {
+        Some(b'x' | b'X') => (16, 1),
+        _ => (10, 0),
+    };
+    let len = data[start..]
+        .iter()
+        .take_while(|b| (**b as char).is_digit(radix))
+        .count();
+    if len == 0 {
+        return None;
+    }
+    let mut end = start + len;
+    if data.get(end) == Some(&b';') {
+        end += 1;
+    }
+
+    let digits = std::str::from_utf8(&data[start..start + len]).unwrap();
+    let c = match u32::from_str_radix(digits, radix) {
+        Ok(n @ 0x80..=0x9f) => WINDOWS_1252[n as usize - 0x80],
+        Ok(n) if n != 0 => char::from_u32(n).unwrap_or(char::REPLACEMENT_CHARACTER),
+        _ => char::REPLACEMENT_CHARACTER,
+    };
+    Some((c, end))
+}
+
+/// Parses a named reference with `data` starting after `&`, returns the value and the number of
+/// bytes read
+fn parse_named(data: &[u8]) -> Option<(&'static str, usize)> {
+    let len = data
+        .iter()
+        .take(MAX_ENTITY_LEN)
+        .take_while(|b| b.is_ascii_alphanumeric())
+        .count();
+    if data.get(len) == Some(&b';')
+        && let Some(value) = get_entity(&data[..=len])
+    {
+        return Some((value, len + 1));
+    }
+
+    // Legacy entities can be used without semicolon, like `&copy2024`
+    (1..=len)
+        .rev()
+        .find_map(|l| get_entity(&data[..l]).map(|value| (value, l)))
+}
+
+struct HtmlDecoder;
+
+impl Transform for HtmlDecoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        // Keep a possibly incomplete reference for the next call
+        let end = if eof {
+            input.len()
+        } else {
+            let tail = input.len().saturating_sub(MAX_ENTITY_LEN);
+            input[tail..]
+                .iter()
+                .rposition(|b| *b == b'&')
+                .map_or(input.len(), |pos| tail + pos)
+        };
+
+        let mut i = 0;
+        while i < end {
+            if input[i] != b'&' {
+                output.push(input[i]);
+                i += 1;
+                continue;
+            }
+
+            let rest = &input[i + 1..];
+            let mut buf = [0u8; 4];
+            let reference = match rest.strip_prefix(b"#") {
+                Some(numeric) => {
+                    parse_numeric(numeric).map(|(c, len)| (&*c.encode_utf8(&mut buf), len + 1))
+                }
+                None => parse_named(rest),
+            };
+            match reference {
+                Some((value, len)) => {
+                    output.extend_from_slice(value.as_bytes());
+                    i += 1 + len;
+                }
+                None => {
+                    output.push(b'&');
+                    i += 1;
+                }
+            }
+        }
+
+        Ok(end)
+    }
+}
+
+struct HtmlEncoder {
+    non_ascii: bool,
+    buffer: String,
+}
+
+fn escape_ascii(b: u8) -> Option<&'static str> {
+    match b {
+        b'&' => Some("&amp;"),
+        b'<' => Some("&lt;"),
+        b'>' => Some("&gt;"),
+        b'"' => Some("&quot;"),
+        b'\'' => Some("&#39;"),
+        _ => None,
+    }
+}
+
+impl Transform for HtmlEncoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        if !self.non_ascii {
+            // Only ASCII characters are escaped, no need to care about UTF-8
+            for b in input {
+                match escape_ascii(*b) {
+                    Some(escaped) => output.extend_from_slice(escaped.as_bytes()),
+                    None => output.push(*b),
+                }
+            }
+            return Ok(input.len());
+        }
+
+        let text = match std::str::from_utf8(input) {
+            Ok(text) => text,
+            // Character cut at the end of the chunk
+            Err(e) if e.error_len().is_none() && !eof => {
+                std::str::from_utf8(&input[..e.valid_up_to()]).unwrap()
+            }
+            Err(e) => return Err(e.into()),
+        };
+        for c in text.chars() {
+            if c.is_ascii() {
+                match escape_ascii(c as u8) {
+                    Some(escaped) => output.extend_from_slice(escaped.as_bytes()),
+                    None => output.push(c as u8),
+                }
+            } else {
+                self.buffer.clear();
+                write!(&mut self.buffer, "&#x{:x};", c as u32).unwrap();
+                output.extend_from_slice(self.buffer.as_bytes());
+            }
+        }
+        Ok(text.len())
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct HtmlCodec {
+    /// Also escape all non-ASCII characters with numeric references
+    non_ascii: bool,
+}
+
+impl HtmlCodec {
+    fn new_encoder(&self) -> HtmlEncoder {
+        HtmlEncoder {
+            non_ascii: self.non_ascii,
+            buffer: String::new(),
+        }
+    }
+}
+
+impl Codec for HtmlCodec {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn description(&self) -> &'static str {
+        "HTML/XML entities, args: minimal (default) or nonascii"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(self.new_encoder(), data, output)
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(HtmlDecoder, data, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let non_ascii = match args {
+            "" | "minimal" => false,
+            "nonascii" => true,
+            _ => return None,
+        };
+        Some(Box::new(Self { non_ascii }) as super::Plugin)
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        data.iter().enumerate().any(|(i, b)| {
+            let rest = &data[i + 1..];
+            *b == b'&'
+                && match rest.strip_prefix(b"#") {
+                    Some(numeric) => {
+                        matches!(parse_numeric(numeric), Some((_, len)) if numeric[len - 1] == b';')
+                    }
+                    None => matches!(parse_named(rest), Some((_, len)) if rest[len - 1] == b';'),
+                }
+        })
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, self.new_encoder()))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, HtmlDecoder))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entities_are_sorted() {
+        assert!(ENTITIES.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn decode() {
+        let codec = HtmlCodec::default();
+        assert_eq!(
+            codec
+                .decode(b"&lt;a href=&quot;?a=1&amp;b=2&quot;&gt;&#65;&#x42;&#X43;&eacute;&#128;&NotNestedGreaterGreater; &copy2024 &unknown; AT&T")
+                .unwrap(),
+            "<a href=\"?a=1&b=2\">ABCé€\u{2aa2}\u{338} ©2024 &unknown; AT&T".as_bytes()
+        );
+    }
+
+    #[test]
+    fn encode() {
+        let minimal = HtmlCodec::default();
+        let non_ascii = HtmlCodec { non_ascii: true };
+        let text = "<p class='x'>café & thé</p>";
+        assert_eq!(
+            minimal.encode(text.as_bytes()).unwrap(),
+            "&lt;p class=&#39;x&#39;&gt;café &amp; thé&lt;/p&gt;".as_bytes()
+        );
+        assert_eq!(
+            non_ascii.encode(text.as_bytes()).unwrap(),
+            b"&lt;p class=&#39;x&#39;&gt;caf&#xe9; &amp; th&#xe9;&lt;/p&gt;"
+        );
+    }
+}
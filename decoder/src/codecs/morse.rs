@@ -0,0 +1,332 @@
+use crate::codecs::{Codec, CodecError, Result};
+
+/// ITU Morse code, plus a few common non-ITU punctuation marks
+const TABLE: [(u8, &str); 54] = [
+    (b'a', ".-"),
+    (b'b', "-..."),
+    (b'c', "-.-."),
+    (b'd', "-.."),
+    (b'e', "."),
+    (b'f', "..-."),
+    (b'g', "--."),
+    (b'h', "...."),
+    (b'i', ".."),
+    (b'j', ".---"),
+    (b'k', "-.-"),
+    (b'l', ".-.."),
+    (b'm', "--"),
+    (b'n', "-."),
+    (b'o', "---"),
+    (b'p', ".--."),
+    (b'q', "--.-"),
+    (b'r', ".-."),
+    (b's', "..."),
+    (b't', "-"),
+    (b'u', "..-"),
+    (b'v', "...-"),
+    (b'w', ".--"),
+    (b'x', "-..-"),
+    (b'y', "-.--"),
+    (b'z', "--.."),
+    (b'0', "-----"),
+    (b'1', ".----"),
+    (b'2', "..---"),
+    (b'3', "...--"),
+    (b'4', "....-"),
+    (b'5', "....."),
+    (b'6', "-...."),
+    (b'7', "--..."),
+    (b'8', "---.."),
+    (b'9', "----."),
+    (b'.', ".-.-.-"),
+    (b',', "--..--"),
+    (b'?', "..--.."),
+    (b'\'', ".----."),
+    (b'!', "-.-.--"),
+    (b'/', "-..-."),
+    (b'(', "-.--."),
+    (b')', "-.--.-"),
+    (b'&', ".-..."),
+    (b':', "---..."),
+    (b';', "-.-.-."),
+    (b'=', "-...-"),
+    (b'+', ".-.-."),
+    (b'-', "-....-"),
+    (b'_', "..--.-"),
+    (b'"', ".-..-."),
+    (b'$', "...-..-"),
+    (b'@', ".--.-."),
+];
+
+const DOTS: &[char] = &['.', '·', '•'];
+const DASHES: &[char] = &['-', '−', '_'];
+
+/// Spoken syllables, longest first so that `dit` is not read as `di` followed by `t`
+const SYLLABLES: [(&str, char); 4] = [("dah", '-'), ("dit", '.'), ("da", '-'), ("di", '.')];
+
+fn parse_separator(name: &str) -> Option<char> {
+    match name {
+        "space" | " " => Some(' '),
+        "slash" | "/" => Some('/'),
+        "pipe" | "|" => Some('|'),
+        _ => None,
+    }
+}
+
+/// Input written as `di-dah-dit` rather than with symbols
+fn is_spelled(text: &str) -> bool {
+    let text = text.to_ascii_lowercase();
+    text.contains("dah") || text.contains("dit")
+}
+
+/// Converts syllables to dots and dashes, hyphens joining them are dropped
+fn unspell(text: &str) -> Result<String> {
+    let mut output = String::with_capacity(text.len() / 2);
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if !c.is_ascii_alphabetic() {
+            let joins = c == '-'
+                && output.ends_with(['.', '-'])
+                && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic());
+            if !joins {
+                output.push(c);
+            }
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+
+        let (syllable, symbol) = SYLLABLES
+            .iter()
+            .find(|(syllable, _)| {
+                rest.get(..syllable.len())
+                    .is_some_and(|s| s.eq_ignore_ascii_case(syllable))
+            })
+            .ok_or(CodecError::InvalidChar(c as u8))?;
+        output.push(*symbol);
+        rest = &rest[syllable.len()..];
+    }
+    Ok(output)
+}
+
+#[derive(Clone)]
+pub struct MorseCodec {
+    /// Written between letters
+    letter: char,
+
+    /// Written between words
+    word: char,
+}
+
+impl Default for MorseCodec {
+    fn default() -> Self {
+        Self {
+            letter: ' ',
+            word: '/',
+        }
+    }
+}
+
+impl MorseCodec {
+    fn word_separator(&self) -> String {
+        if self.word == self.letter {
+            self.word.to_string().repeat(2)
+        } else if self.letter == ' ' {
+            format!(" {} ", self.word)
+        } else {
+            self.word.to_string()
+        }
+    }
+
+    fn push_letter(code: &mut String, word_gap: &mut bool, output: &mut Vec<u8>) -> Result<()> {
+        if code.is_empty() {
+            return Ok(());
+        }
+        let (letter, _) = TABLE
+            .iter()
+            .find(|(_, c)| c == code)
+            .ok_or(CodecError::InvalidGroup)?;
+        if *word_gap && !output.is_empty() && !output.ends_with(b"\n") {
+            output.push(b' ');
+        }
+        output.push(*letter);
+        code.clear();
+        *word_gap = false;
+        Ok(())
+    }
+}
+
+impl Codec for MorseCodec {
+    fn name(&self) -> &'static str {
+        "morse"
+    }
+
+    fn description(&self) -> &'static str {
+        "ITU Morse code, also decodes di-dah spellings, args: letter=SEP and word=SEP where SEP \
+         is space, slash or pipe"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let word_separator = self.word_separator();
+        for (i, line) in data.split(|b| *b == b'\n').enumerate() {
+            if i > 0 {
+                output.push(b'\n');
+            }
+            let words = line
+                .split(|b| b.is_ascii_whitespace())
+                .filter(|w| !w.is_empty());
+            for (j, word) in words.enumerate() {
+                if j > 0 {
+                    output.extend_from_slice(word_separator.as_bytes());
+                }
+                for (k, b) in word.iter().enumerate() {
+                    let b = b.to_ascii_lowercase();
+                    let (_, code) = TABLE.iter().find(|(c, _)| *c == b).ok_or(if b.is_ascii() {
+                        CodecError::InvalidChar(b)
+                    } else {
+                        CodecError::NonAsciiChar(b)
+                    })?;
+                    if k > 0 {
+                        output.extend(self.letter.to_string().bytes());
+                    }
+                    output.extend_from_slice(code.as_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let text = std::str::from_utf8(data)?;
+        let text = if is_spelled(text) {
+            unspell(text)?
+        } else {
+            text.to_owned()
+        };
+
+        let mut code = String::new();
+        let mut word_gap = false;
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if DOTS.contains(&c) {
+                code.push('.');
+            } else if DASHES.contains(&c) {
+                code.push('-');
+            } else if c == '\n' {
+                Self::push_letter(&mut code, &mut word_gap, output)?;
+                output.push(b'\n');
+                word_gap = false;
+            } else if c.is_whitespace() {
+                Self::push_letter(&mut code, &mut word_gap, output)?;
+                // A single space between letters, anything longer between words
+                let mut len = 1;
+                while chars.next_if(|c| *c != '\n' && c.is_whitespace()).is_some() {
+                    len += 1;
+                }
+                word_gap |= self.letter != ' ' || len > 1;
+            } else if c == '/' || c == '|' {
+                Self::push_letter(&mut code, &mut word_gap, output)?;
+                // Doubled letter separators are word separators
+                let mut len = 1;
+                while chars.next_if_eq(&c).is_some() {
+                    len += 1;
+                }
+                word_gap |= c != self.letter || len > 1;
+            } else {
+                return Err(match u8::try_from(c) {
+                    Ok(b) => CodecError::InvalidChar(b),
+                    Err(_) => CodecError::NonAsciiChar(c.encode_utf8(&mut [0; 4]).as_bytes()[0]),
+                });
+            }
+        }
+        Self::push_letter(&mut code, &mut word_gap, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let mut codec = Self::default();
+        for arg in args.split(',').filter(|a| !a.is_empty()) {
+            match arg.split_once('=')? {
+                ("letter", separator) => codec.letter = parse_separator(separator)?,
+                ("word", separator) => codec.word = parse_separator(separator)?,
+                _ => return None,
+            }
+        }
+        Some(Box::new(codec) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        size / 4
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        size * 5
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let Ok(text) = std::str::from_utf8(data) else {
+            return false;
+        };
+        let Ok(text) = (if is_spelled(text) {
+            unspell(text)
+        } else {
+            Ok(text.to_owned())
+        }) else {
+            return false;
+        };
+        text.contains(DOTS)
+            && text.chars().all(|c| {
+                DOTS.contains(&c) || DASHES.contains(&c) || c.is_whitespace() || "/|".contains(c)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codec(args: &str) -> crate::codecs::Plugin {
+        MorseCodec::default().build(args).unwrap()
+    }
+
+    #[test]
+    fn encode() {
+        let codec = MorseCodec::default();
+        assert_eq!(
+            codec.encode(b"SOS, help").unwrap(),
+            b"... --- ... --..-- / .... . .-.. .--."
+        );
+        assert_eq!(
+            self::codec("letter=slash,word=pipe")
+                .encode(b"hi all")
+                .unwrap(),
+            b"..../..|.-/.-../.-.."
+        );
+        assert_eq!(
+            self::codec("word=space").encode(b"hi all\nok").unwrap(),
+            b".... ..  .- .-.. .-..\n--- -.-"
+        );
+        assert!(codec.encode(b"#").is_err());
+    }
+
+    #[test]
+    fn decode() {
+        let codec = MorseCodec::default();
+        for input in [
+            "... --- ... / .... . .-.. .--.",
+            "... --- ...  .... . .-.. .--.",
+            "··· −−− ··· / ···· · ·−·· ·−−·",
+            "di-di-dit dah-dah-dah di-di-dit / di-di-di-dit dit di-dah-di-dit di-dah-dah-dit",
+        ] {
+            assert!(codec.detect(input.as_bytes()), "{input}");
+            assert_eq!(
+                codec.decode(input.as_bytes()).unwrap(),
+                b"sos help",
+                "{input}"
+            );
+        }
+        let pipes = self::codec("letter=pipe,word=pipe");
+        assert_eq!(pipes.encode(b"sos w").unwrap(), b"...|---|...||.--");
+        assert_eq!(pipes.decode(b"...|---|...||.--").unwrap(), b"sos w");
+        assert!(codec.decode(b"........").is_err());
+        assert!(!codec.detect(b"hello"));
+    }
+}
@@ -0,0 +1,240 @@
+use std::io::Write;
+
+use crate::codecs::{Codec, CodecError, Result};
+
+/// Nesting limit, deeper length-delimited fields are shown as strings or bytes
+const MAX_DEPTH: usize = 64;
+
+const INDENT: &str = "  ";
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn read_varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = *self.data.get(self.pos).ok_or(CodecError::Truncated)?;
+            self.pos += 1;
+            value |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CodecError::Malformed("Varint is longer than 10 bytes"))
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.pos..)
+            .and_then(|rest| rest.get(..len))
+            .ok_or(CodecError::Truncated)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn read_fixed<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.read_bytes(N)?.try_into().unwrap())
+    }
+
+    /// Writes fields up to the end of the data, or up to the end of `group`
+    fn parse_fields(
+        &mut self,
+        depth: usize,
+        group: Option<u64>,
+        output: &mut Vec<u8>,
+    ) -> Result<()> {
+        let indent = INDENT.repeat(depth);
+        while self.pos < self.data.len() {
+            let key = self.read_varint()?;
+            let field = key >> 3;
+            if field == 0 || field >= 1 << 29 {
+                return Err(CodecError::Malformed("Invalid field number"));
+            }
+
+            match key & 7 {
+                0 => {
+                    let value = self.read_varint()?;
+                    write!(output, "{indent}{field} (varint): {value} (")?;
+                    if (value as i64) < 0 {
+                        write!(output, "int64: {}, ", value as i64)?;
+                    }
+                    let zigzag = (value >> 1) as i64 ^ -((value & 1) as i64);
+                    writeln!(output, "zigzag: {zigzag})")?;
+                }
+                1 => {
+                    let bytes = self.read_fixed::<8>()?;
+                    let value = u64::from_le_bytes(bytes);
+                    write!(output, "{indent}{field} (fixed64): {value} (")?;
+                    if (value as i64) < 0 {
+                        write!(output, "int64: {}, ", value as i64)?;
+                    }
+                    writeln!(output, "double: {})", f64::from_le_bytes(bytes))?;
+                }
+                2 => {
+                    let len = usize::try_from(self.read_varint()?)
+                        .map_err(|_| CodecError::Malformed("Length overflow"))?;
+                    let bytes = self.read_bytes(len)?;
+                    write_length_delimited(field, bytes, depth, output)?;
+                }
+                3 => {
+                    writeln!(output, "{indent}{field} (group) {{")?;
+                    if depth >= MAX_DEPTH {
+                        return Err(CodecError::Malformed("Groups are nested too deeply"));
+                    }
+                    self.parse_fields(depth + 1, Some(field), output)?;
+                    writeln!(output, "{indent}}}")?;
+                }
+                4 if group == Some(field) => return Ok(()),
+                4 => return Err(CodecError::Malformed("Unexpected end of group")),
+                5 => {
+                    let bytes = self.read_fixed::<4>()?;
+                    let value = u32::from_le_bytes(bytes);
+                    write!(output, "{indent}{field} (fixed32): {value} (")?;
+                    if (value as i32) < 0 {
+                        write!(output, "int32: {}, ", value as i32)?;
+                    }
+                    writeln!(output, "float: {})", f32::from_le_bytes(bytes))?;
+                }
+                _ => return Err(CodecError::Malformed("Invalid wire type")),
+            }
+        }
+
+        match group {
+            Some(_) => Err(CodecError::Truncated),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes a nested message if `bytes` parse as one, otherwise a string or bytes
+fn write_length_delimited(
+    field: u64,
+    bytes: &[u8],
+    depth: usize,
+    output: &mut Vec<u8>,
+) -> Result<()> {
+    let indent = INDENT.repeat(depth);
+    if !bytes.is_empty() && depth < MAX_DEPTH {
+        let mut nested = Vec::new();
+        let mut parser = Parser {
+            data: bytes,
+            pos: 0,
+        };
+        if parser.parse_fields(depth + 1, None, &mut nested).is_ok() {
+            writeln!(output, "{indent}{field} (len) {{")?;
+            output.extend_from_slice(&nested[..]);
+            writeln!(output, "{indent}}}")?;
+            return Ok(());
+        }
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => writeln!(output, "{indent}{field} (len): {text:?}")?,
+        Err(_) => writeln!(
+            output,
+            "{indent}{field} (len): b\"{}\"",
+            bytes.escape_ascii()
+        )?,
+    }
+    Ok(())
+}
+
+/// Renders Protobuf messages without their schema, encoding copies the input
+#[derive(Default, Clone)]
+pub struct ProtobufCodec;
+
+impl Codec for ProtobufCodec {
+    fn name(&self) -> &'static str {
+        "protobuf"
+    }
+
+    fn description(&self) -> &'static str {
+        "Protobuf wire format dump like protoc --decode_raw, decode only"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        output.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        if data.is_empty() {
+            return Err(CodecError::Truncated);
+        }
+        Parser { data, pos: 0 }.parse_fields(0, None, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        if !args.is_empty() {
+            return None;
+        }
+        Some(Box::new(Self) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        size * 8
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        // Text rarely parses as a message, but would be dumped as fields when it does
+        !data.is_empty()
+            && !data
+                .iter()
+                .all(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+            && Parser { data, pos: 0 }
+                .parse_fields(0, None, &mut Vec::new())
+                .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_raw() {
+        let message = b"\x08\x96\x01\x10\x03\x19\x1f\x85\xeb\x51\xb8\x1e\x09\x40\
+            \x25\x00\x00\x80\xbf\x2a\x05hello\x32\x04\x08\x01\x10\x02\
+            \x3a\x02\xff\x00\x40\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01\
+            \x4b\x08\x07\x4c";
+        let codec = ProtobufCodec;
+        assert!(codec.detect(message));
+        let dump = String::from_utf8(codec.decode(message).unwrap()).unwrap();
+        assert_eq!(
+            dump,
+            "1 (varint): 150 (zigzag: 75)
+2 (varint): 3 (zigzag: -2)
+3 (fixed64): 4614253070214989087 (double: 3.14)
+4 (fixed32): 3212836864 (int32: -1082130432, float: -1)
+5 (len): \"hello\"
+6 (len) {
+  1 (varint): 1 (zigzag: -1)
+  2 (varint): 2 (zigzag: 1)
+}
+7 (len): b\"\\xff\\x00\"
+8 (varint): 18446744073709551615 (int64: -1, zigzag: -9223372036854775808)
+9 (group) {
+  1 (varint): 7 (zigzag: -4)
+}
+"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let codec = ProtobufCodec;
+        assert!(matches!(
+            codec.decode(b"\x08\x96"),
+            Err(CodecError::Truncated)
+        ));
+        assert!(codec.decode(b"\x0f").is_err());
+        assert!(codec.decode(b"\x00\x01").is_err());
+        assert!(codec.decode(b"\x4c").is_err());
+        assert!(!codec.detect(b"hello world"));
+        assert!(codec.detect(b"\x08\x01"));
+    }
+}
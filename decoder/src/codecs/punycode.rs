@@ -0,0 +1,315 @@
+use crate::codecs::{Codec, CodecError, Result};
+
+// Bootstring parameters for Punycode, see RFC 3492 section 5
+const BASE: u32 = 36;
+const TMIN: u32 = 1;
+const TMAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 0x80;
+const DELIMITER: char = '-';
+
+/// Prefix of internationalized labels in domain names
+const ACE_PREFIX: &str = "xn--";
+
+fn adapt(delta: u32, num_points: u32, first_time: bool) -> u32 {
+    let mut delta = if first_time { delta / DAMP } else { delta / 2 };
+    delta += delta / num_points;
+
+    let mut k = 0;
+    while delta > ((BASE - TMIN) * TMAX) / 2 {
+        delta /= BASE - TMIN;
+        k += BASE;
+    }
+    k + (((BASE - TMIN + 1) * delta) / (delta + SKEW))
+}
+
+fn threshold(k: u32, bias: u32) -> u32 {
+    k.saturating_sub(bias).clamp(TMIN, TMAX)
+}
+
+fn decode_digit(c: char) -> Result<u32> {
+    match c {
+        'a'..='z' => Ok(c as u32 - 'a' as u32),
+        'A'..='Z' => Ok(c as u32 - 'A' as u32),
+        '0'..='9' => Ok(c as u32 - '0' as u32 + 26),
+        _ if c.is_ascii() => Err(CodecError::InvalidChar(c as u8)),
+        _ => Err(CodecError::NonAsciiChar(
+            c.encode_utf8(&mut [0; 4]).as_bytes()[0],
+        )),
+    }
+}
+
+fn overflow() -> CodecError {
+    CodecError::Malformed("Punycode value overflow")
+}
+
+fn encode_digit(d: u32) -> char {
+    match d {
+        0..=25 => char::from(b'a' + d as u8),
+        _ => char::from(b'0' + (d - 26) as u8),
+    }
+}
+
+/// Decodes a Punycode string, without the `xn--` prefix
+pub fn decode(input: &str) -> Result<String> {
+    let (basic, extended) = match input.rfind(DELIMITER) {
+        Some(pos) => (&input[..pos], &input[pos + 1..]),
+        None => ("", input),
+    };
+    if let Some(b) = basic.bytes().find(|b| !b.is_ascii()) {
+        return Err(CodecError::NonAsciiChar(b));
+    }
+
+    let mut output: Vec<char> = basic.chars().collect();
+    let mut digits = extended.chars();
+    let (mut n, mut i, mut bias) = (INITIAL_N, 0u32, INITIAL_BIAS);
+
+    while digits.as_str() != "" {
+        let old_i = i;
+        let mut w = 1u32;
+        let mut k = BASE;
+        loop {
+            let digit = decode_digit(digits.next().ok_or(CodecError::Truncated)?)?;
+            i = digit
+                .checked_mul(w)
+                .and_then(|d| i.checked_add(d))
+                .ok_or_else(overflow)?;
+            let t = threshold(k, bias);
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t).ok_or_else(overflow)?;
+            k += BASE;
+        }
+
+        let len = output.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len).ok_or_else(overflow)?;
+        i %= len;
+        let c = char::from_u32(n).ok_or(CodecError::Malformed("Invalid code point"))?;
+        output.insert(i as usize, c);
+        i += 1;
+    }
+
+    Ok(output.into_iter().collect())
+}
+
+/// Encodes `input` to Punycode, without the `xn--` prefix
+pub fn encode(input: &str) -> Result<String> {
+    let code_points: Vec<u32> = input.chars().map(u32::from).collect();
+    let mut output: String = input.chars().filter(char::is_ascii).collect();
+    let basic_len = output.len() as u32;
+    if basic_len > 0 {
+        output.push(DELIMITER);
+    }
+
+    let (mut n, mut delta, mut bias) = (INITIAL_N, 0u32, INITIAL_BIAS);
+    let mut handled = basic_len;
+    while (handled as usize) < code_points.len() {
+        let m = *code_points.iter().filter(|c| **c >= n).min().unwrap();
+        delta = (m - n)
+            .checked_mul(handled + 1)
+            .and_then(|d| delta.checked_add(d))
+            .ok_or_else(overflow)?;
+        n = m;
+
+        for c in &code_points {
+            if *c < n {
+                delta = delta.checked_add(1).ok_or_else(overflow)?;
+            } else if *c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+                    output.push(encode_digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push(encode_digit(q));
+                bias = adapt(delta, handled + 1, handled == basic_len);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta += 1;
+        n += 1;
+    }
+
+    Ok(output)
+}
+
+fn is_label_separator(c: char) -> bool {
+    // Full stops accepted by IDNA besides '.'
+    c.is_whitespace() || matches!(c, '.' | '\u{3002}' | '\u{ff0e}' | '\u{ff61}')
+}
+
+/// Applies `convert` to every run of characters satisfying `in_label`, keeping the rest
+fn map_labels(
+    text: &str,
+    in_label: impl Fn(char) -> bool,
+    mut convert: impl FnMut(&str) -> Result<String>,
+    output: &mut Vec<u8>,
+) -> Result<()> {
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = rest.find(|c| !in_label(c)).unwrap_or(rest.len());
+        let (label, after) = rest.split_at(end);
+        if !label.is_empty() {
+            output.extend_from_slice(convert(label)?.as_bytes());
+        }
+
+        let Some(separator) = after.chars().next() else {
+            break;
+        };
+        output.extend_from_slice(separator.encode_utf8(&mut [0; 4]).as_bytes());
+        rest = &after[separator.len_utf8()..];
+    }
+    Ok(())
+}
+
+fn strip_ace_prefix(label: &str) -> Option<&str> {
+    let prefix = label.get(..ACE_PREFIX.len())?;
+    prefix
+        .eq_ignore_ascii_case(ACE_PREFIX)
+        .then(|| &label[ACE_PREFIX.len()..])
+}
+
+/// Punycode, as used by internationalized domain names
+#[derive(Default, Clone)]
+pub struct PunycodeCodec {
+    /// Convert `xn--` labels of domain names instead of the whole input
+    idna: bool,
+}
+
+impl PunycodeCodec {
+    fn decode_label(label: &str) -> Result<String> {
+        let Some(encoded) = strip_ace_prefix(label) else {
+            return Ok(label.to_owned());
+        };
+        let decoded = decode(encoded)?;
+
+        // Show what hides behind lookalike characters
+        let code_points: Vec<_> = decoded
+            .chars()
+            .filter(|c| !c.is_ascii())
+            .map(|c| format!("U+{:04X}", u32::from(c)))
+            .collect();
+        eprintln!("{label}: {decoded} ({})", code_points.join(" "));
+        Ok(decoded)
+    }
+
+    fn encode_label(label: &str) -> Result<String> {
+        if label.is_ascii() {
+            Ok(label.to_owned())
+        } else {
+            Ok(format!("{ACE_PREFIX}{}", encode(label)?))
+        }
+    }
+}
+
+impl Codec for PunycodeCodec {
+    fn name(&self) -> &'static str {
+        "punycode"
+    }
+
+    fn description(&self) -> &'static str {
+        "Punycode (RFC 3492), args: idna to convert xn-- labels of domain names"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let text = std::str::from_utf8(data)?;
+        if !self.idna {
+            output.extend_from_slice(encode(text)?.as_bytes());
+            return Ok(());
+        }
+
+        map_labels(
+            text,
+            |c| !is_label_separator(c) && (!c.is_ascii() || c.is_ascii_alphanumeric() || c == '-'),
+            Self::encode_label,
+            output,
+        )
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let text = std::str::from_utf8(data)?;
+        if !self.idna {
+            output.extend_from_slice(decode(text.trim_ascii_end())?.as_bytes());
+            return Ok(());
+        }
+
+        map_labels(
+            text,
+            |c| !is_label_separator(c) && (c.is_alphanumeric() || c == '-'),
+            Self::decode_label,
+            output,
+        )
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let idna = match args {
+            "" => false,
+            "idna" => true,
+            _ => return None,
+        };
+        Some(Box::new(Self { idna }) as super::Plugin)
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        // Raw Punycode cannot be told apart from regular text
+        self.idna
+            && data
+                .split(|b| !(b.is_ascii_alphanumeric() || *b == b'-'))
+                .any(|label| {
+                    label.len() > ACE_PREFIX.len()
+                        && label[..ACE_PREFIX.len()].eq_ignore_ascii_case(ACE_PREFIX.as_bytes())
+                })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc3492_samples() {
+        let samples = [
+            ("ليهمابتكلموشعربي؟", "egbpdaj6bu4bxfgehfvwxn"),
+            ("他们为什么不说中文", "ihqwcrb4cv8a8dqg056pqjye"),
+            ("Pročprostěnemluvíčesky", "Proprostnemluvesky-uyb24dma41a"),
+            ("3年B組金八先生", "3B-ww4c5e180e575a65lsy2b"),
+            ("MajiでKoiする5秒前", "MajiKoi5-783gue6qz075azm5e"),
+            ("bücher", "bcher-kva"),
+        ];
+        for (text, encoded) in samples {
+            assert_eq!(encode(text).unwrap(), encoded);
+            assert_eq!(decode(encoded).unwrap(), text);
+        }
+        assert!(decode("a-!").is_err());
+        assert!(matches!(decode("bcher-k"), Err(CodecError::Truncated)));
+    }
+
+    #[test]
+    fn idna() {
+        let codec = PunycodeCodec { idna: true };
+        assert_eq!(
+            codec
+                .decode(b"https://xn--pple-43d.com/login and XN--bcher-kva.example")
+                .unwrap(),
+            "https://аpple.com/login and bücher.example".as_bytes()
+        );
+        assert_eq!(
+            codec
+                .encode("http://bücher.example/index".as_bytes())
+                .unwrap(),
+            b"http://xn--bcher-kva.example/index"
+        );
+        assert!(codec.detect(b"www.xn--80ak6aa92e.com"));
+        assert!(!codec.detect(b"xn--"));
+    }
+}
@@ -0,0 +1,275 @@
+use crate::codecs::{
+    stream::{transform_all, Transform, TransformReader},
+    Codec, CodecError, Result, Stream,
+};
+
+/// Encoded lines are at most 76 characters long, including the `=` of soft line breaks
+const MAX_LINE_LEN: usize = 76;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+fn escape(b: u8) -> [u8; 3] {
+    [
+        b'=',
+        HEX_DIGITS[usize::from(b >> 4)],
+        HEX_DIGITS[usize::from(b & 0xf)],
+    ]
+}
+
+fn get_hex_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct QuotedPrintableCodec {
+    /// Keep malformed `=` sequences as-is instead of failing
+    lenient: bool,
+
+    /// Input is binary, line breaks are encoded like any other byte
+    binary: bool,
+}
+
+struct QuotedPrintableEncoder {
+    binary: bool,
+    column: usize,
+    crlf: bool,
+}
+
+impl QuotedPrintableEncoder {
+    fn push(&mut self, encoded: &[u8], output: &mut Vec<u8>) {
+        if self.column + encoded.len() > MAX_LINE_LEN - 1 {
+            output.push(b'=');
+            self.push_newline(self.crlf, output);
+        }
+        output.extend_from_slice(encoded);
+        self.column += encoded.len();
+    }
+
+    /// Soft line breaks use the same style as the last hard one
+    fn push_newline(&mut self, crlf: bool, output: &mut Vec<u8>) {
+        self.crlf = crlf;
+        if crlf {
+            output.push(b'\r');
+        }
+        output.push(b'\n');
+        self.column = 0;
+    }
+}
+
+impl Transform for QuotedPrintableEncoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        let mut i = 0;
+        while i < input.len() {
+            let b = input[i];
+            let next = input.get(i + 1).copied();
+            if next.is_none() && !eof && matches!(b, b' ' | b'\t' | b'\r') {
+                // Need to know what follows to encode it
+                return Ok(i);
+            }
+
+            match (b, next) {
+                (b'\n', _) if !self.binary => {
+                    self.push_newline(false, output);
+                }
+                (b'\r', Some(b'\n')) if !self.binary => {
+                    self.push_newline(true, output);
+                    i += 1;
+                }
+                // Whitespaces at the end of a line would be stripped by decoders
+                (b' ' | b'\t', Some(b'\n' | b'\r') | None) => {
+                    self.push(&escape(b), output);
+                }
+                (b' ' | b'\t' | b'!'..=b'<' | b'>'..=b'~', _) => self.push(&[b], output),
+                _ => {
+                    self.push(&escape(b), output);
+                }
+            }
+            i += 1;
+        }
+
+        Ok(input.len())
+    }
+}
+
+struct QuotedPrintableDecoder {
+    lenient: bool,
+}
+
+impl QuotedPrintableDecoder {
+    fn decode_line(&self, line: &[u8], newline: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        // Trailing whitespaces were added in transport
+        let end = line
+            .iter()
+            .rposition(|b| !matches!(b, b' ' | b'\t'))
+            .map_or(0, |pos| pos + 1);
+        let line = &line[..end];
+
+        let mut i = 0;
+        while i < line.len() {
+            let b = line[i];
+            if b != b'=' {
+                output.push(b);
+                i += 1;
+                continue;
+            }
+
+            match (line.get(i + 1), line.get(i + 2)) {
+                // Soft line break
+                (None, _) => return Ok(()),
+                (Some(high), Some(low)) => match (get_hex_value(*high), get_hex_value(*low)) {
+                    (Some(high), Some(low)) => {
+                        output.push((high << 4) | low);
+                        i += 3;
+                        continue;
+                    }
+                    _ if self.lenient => {}
+                    (None, _) => return Err(CodecError::InvalidHexDigit(*high)),
+                    (_, None) => return Err(CodecError::InvalidHexDigit(*low)),
+                },
+                (Some(high), None) if !self.lenient => {
+                    return Err(CodecError::InvalidHexDigit(*high));
+                }
+                _ => {}
+            }
+            output.push(b);
+            i += 1;
+        }
+
+        output.extend_from_slice(newline);
+        Ok(())
+    }
+}
+
+impl Transform for QuotedPrintableDecoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        let mut consumed = 0;
+        for line in input.split_inclusive(|b| *b == b'\n') {
+            let Some(line) = line.strip_suffix(b"\n") else {
+                if eof {
+                    self.decode_line(line, b"", output)?;
+                    consumed += line.len();
+                }
+                break;
+            };
+            let (line, newline) = match line.strip_suffix(b"\r") {
+                Some(line) => (line, &b"\r\n"[..]),
+                None => (line, &b"\n"[..]),
+            };
+            self.decode_line(line, newline, output)?;
+            consumed += line.len() + newline.len();
+        }
+        Ok(consumed)
+    }
+}
+
+impl QuotedPrintableCodec {
+    fn new_encoder(&self) -> QuotedPrintableEncoder {
+        QuotedPrintableEncoder {
+            binary: self.binary,
+            column: 0,
+            crlf: false,
+        }
+    }
+
+    fn new_decoder(&self) -> QuotedPrintableDecoder {
+        QuotedPrintableDecoder {
+            lenient: self.lenient,
+        }
+    }
+}
+
+impl Codec for QuotedPrintableCodec {
+    fn name(&self) -> &'static str {
+        "quoted-printable"
+    }
+
+    fn description(&self) -> &'static str {
+        "MIME quoted-printable, args: lenient, binary"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(self.new_encoder(), data, output)
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(self.new_decoder(), data, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let mut codec = Self::default();
+        for arg in args.split(',').filter(|a| !a.is_empty()) {
+            match arg {
+                "lenient" => codec.lenient = true,
+                "binary" => codec.binary = true,
+                _ => return None,
+            }
+        }
+        Some(Box::new(codec) as super::Plugin)
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        size + size / 4
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        // Look for at least one soft line break or escape, otherwise it's plain text
+        let has_escapes = data.windows(3).any(|w| {
+            w[0] == b'='
+                && ((w[1].is_ascii_hexdigit() && w[2].is_ascii_hexdigit())
+                    || w[1] == b'\n'
+                    || &w[1..] == b"\r\n")
+        });
+        has_escapes
+            && data
+                .iter()
+                .all(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, self.new_encoder()))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, self.new_decoder()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soft_line_breaks() {
+        let codec = QuotedPrintableCodec::default();
+        let text = "J'interdis aux marchands de vanter trop leurs marchandises. Car ils se font vite pédagogues et t'enseignent comme but ce qui n'est par essence qu'un moyen.\n";
+        let encoded = codec.encode(text.as_bytes()).unwrap();
+        assert!(encoded
+            .split(|b| *b == b'\n')
+            .all(|line| line.len() <= MAX_LINE_LEN + 1));
+        assert!(encoded.starts_with(b"J'interdis aux marchands de vanter trop leurs marchandises. Car ils se font=\n vite p=C3=A9dagogues"));
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), text.as_bytes());
+    }
+
+    #[test]
+    fn trailing_whitespaces() {
+        let codec = QuotedPrintableCodec::default();
+        assert_eq!(codec.encode(b"a \nb\t").unwrap(), b"a=20\nb=09");
+        assert_eq!(codec.decode(b"a=20  \nb =\nc").unwrap(), b"a \nb c");
+    }
+
+    #[test]
+    fn lenient() {
+        let strict = QuotedPrintableCodec::default();
+        let lenient = QuotedPrintableCodec {
+            lenient: true,
+            binary: false,
+        };
+        assert!(strict.decode(b"1 =+ 1").is_err());
+        assert_eq!(lenient.decode(b"1 =+ 1 =3D 2=z").unwrap(), b"1 =+ 1 = 2=z");
+    }
+}
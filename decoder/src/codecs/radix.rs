@@ -0,0 +1,362 @@
+use crate::codecs::{score::plausibility, Codec, CodecError, Result};
+
+/// Radixes tried when decoding numbers without prefix
+const GUESSED_RADIXES: [u32; 4] = [2, 8, 10, 16];
+
+/// Characters splitting numbers when no separator is given
+const DEFAULT_SEPARATORS: &str = ",;[](){}";
+
+/// Number of digits needed to write any byte
+fn byte_width(radix: u32) -> usize {
+    let mut width = 1;
+    let mut max = radix;
+    while max <= 0xff {
+        max *= radix;
+        width += 1;
+    }
+    width
+}
+
+/// Removes a `0b`, `0o` or `0x` prefix, returns the radix it stands for. A prefix is only
+/// recognized when it matches the expected radix, as `0b` is also a valid start for
+/// hexadecimal numbers.
+fn strip_prefix(number: &str, expected: Option<u32>) -> Result<(Option<u32>, &str)> {
+    let radix = match number.get(..2) {
+        Some("0b" | "0B") => 2,
+        Some("0o" | "0O") => 8,
+        Some("0x" | "0X") => 16,
+        _ => return Ok((None, number)),
+    };
+    if expected.is_some_and(|expected| expected != radix) {
+        return Ok((None, number));
+    }
+    match &number[2..] {
+        "" => Err(CodecError::Malformed("Prefix without digits")),
+        digits => Ok((Some(radix), digits)),
+    }
+}
+
+fn prefix(radix: u32) -> &'static str {
+    match radix {
+        2 => "0b",
+        8 => "0o",
+        16 => "0x",
+        _ => "",
+    }
+}
+
+fn parse_digit(c: char, radix: u32) -> Result<u32> {
+    c.to_digit(radix).ok_or(match u8::try_from(c) {
+        Ok(b) => CodecError::InvalidChar(b),
+        Err(_) => CodecError::NonAsciiChar(c.encode_utf8(&mut [0; 4]).as_bytes()[0]),
+    })
+}
+
+/// Big endian bytes of the number written with `digits`
+fn bytes_from_digits(digits: &str, radix: u32) -> Result<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in digits.chars() {
+        let mut carry = parse_digit(c, radix)?;
+        for b in bytes.iter_mut().rev() {
+            let value = u32::from(*b) * radix + carry;
+            *b = value as u8;
+            carry = value >> 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Digits of the big endian number in `bytes`
+fn digits_from_bytes(bytes: &[u8], radix: u32) -> String {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    let mut number = bytes[start..].to_vec();
+    let mut digits = Vec::new();
+    while !number.is_empty() {
+        let mut remainder = 0;
+        for b in number.iter_mut() {
+            let value = (remainder << 8) | u32::from(*b);
+            *b = (value / radix) as u8;
+            remainder = value % radix;
+        }
+        digits.push(char::from_digit(remainder, radix).unwrap());
+
+        let start = number.iter().position(|b| *b != 0).unwrap_or(number.len());
+        number.drain(..start);
+    }
+
+    if digits.is_empty() {
+        digits.push('0');
+    }
+    digits.iter().rev().collect()
+}
+
+fn parse_separator(name: &str) -> String {
+    match name {
+        "space" => " ",
+        "comma" => ",",
+        "newline" => "\n",
+        "tab" => "\t",
+        "none" => "",
+        _ => name,
+    }
+    .to_owned()
+}
+
+/// Lists of numbers, one per byte, or a single big integer
+#[derive(Default, Clone)]
+pub struct RadixCodec {
+    /// Base of the numbers, guessed from the input when decoding if not set
+    radix: Option<u32>,
+
+    /// Separator between numbers, whitespace and punctuation when decoding if not set
+    separator: Option<String>,
+
+    /// Write `0b`, `0o` or `0x` before numbers
+    prefix: bool,
+
+    /// The whole input is a single integer
+    bigint: bool,
+}
+
+impl RadixCodec {
+    fn split_numbers<'a>(&self, text: &'a str) -> Result<Vec<&'a str>> {
+        let numbers = match self.separator.as_deref() {
+            // Numbers have a fixed width without separator
+            Some("") => {
+                let width = byte_width(self.radix.unwrap_or(10));
+                let text = text.trim();
+                if let Some(b) = text.bytes().find(|b| !b.is_ascii()) {
+                    return Err(CodecError::NonAsciiChar(b));
+                }
+                (0..text.len())
+                    .step_by(width)
+                    .map(|i| &text[i..text.len().min(i + width)])
+                    .collect()
+            }
+            Some(separator) => text
+                .trim()
+                .split(separator)
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .collect(),
+            None => text
+                .split(|c: char| c.is_whitespace() || DEFAULT_SEPARATORS.contains(c))
+                .filter(|n| !n.is_empty())
+                .collect(),
+        };
+        Ok(numbers)
+    }
+
+    fn decode_numbers(&self, numbers: &[&str], radix: u32) -> Result<Vec<u8>> {
+        let mut output = Vec::with_capacity(numbers.len());
+        for number in numbers {
+            let (prefix_radix, digits) = strip_prefix(number, self.radix)?;
+            let value = bytes_from_digits(digits, prefix_radix.unwrap_or(radix))?;
+            match value[..] {
+                [] => output.push(0),
+                [b] => output.push(b),
+                _ => return Err(CodecError::InvalidGroup),
+            }
+        }
+        Ok(output)
+    }
+
+    fn decode_list(&self, text: &str) -> Result<Vec<u8>> {
+        let numbers = self.split_numbers(text)?;
+        if let Some(radix) = self.radix {
+            return self.decode_numbers(&numbers[..], radix);
+        }
+
+        // Keep the most plausible radix, the first one wins ties
+        let mut best: Option<(u32, f64, Vec<u8>)> = None;
+        let mut error = CodecError::NoCodecAvailable;
+        for radix in GUESSED_RADIXES {
+            match self.decode_numbers(&numbers[..], radix) {
+                Ok(output) => {
+                    let score = plausibility(text.as_bytes(), &output[..]);
+                    if best.as_ref().is_none_or(|(_, s, _)| score > *s) {
+                        best = Some((radix, score, output));
+                    }
+                }
+                Err(e) => error = e,
+            }
+        }
+
+        let (radix, _, output) = best.ok_or(error)?;
+        eprintln!("Numbers are in base {radix}");
+        Ok(output)
+    }
+
+    fn decode_bigint(&self, text: &str) -> Result<Vec<u8>> {
+        let digits: String = text
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '_')
+            .collect();
+        let (prefix_radix, digits) = strip_prefix(&digits, self.radix)?;
+        let radix = self.radix.or(prefix_radix).unwrap_or_else(|| {
+            if digits.chars().all(|c| c.is_ascii_digit()) {
+                10
+            } else {
+                16
+            }
+        });
+        bytes_from_digits(digits, radix)
+    }
+}
+
+impl Codec for RadixCodec {
+    fn name(&self) -> &'static str {
+        "radix"
+    }
+
+    fn description(&self) -> &'static str {
+        "Numbers list, args: RADIX (guessed), sep=SEP (space, comma, newline, tab, none or a \
+         string), prefix, bigint to convert the whole input to a single number"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let radix = self.radix.unwrap_or(10);
+        let prefix = if self.prefix { prefix(radix) } else { "" };
+        if self.bigint {
+            output.extend_from_slice(prefix.as_bytes());
+            output.extend_from_slice(digits_from_bytes(data, radix).as_bytes());
+            return Ok(());
+        }
+
+        let separator = self.separator.as_deref().unwrap_or(" ");
+        // Decimal numbers are only padded when nothing separates them
+        let width = if radix != 10 || separator.is_empty() {
+            byte_width(radix)
+        } else {
+            1
+        };
+        for (i, b) in data.iter().enumerate() {
+            if i > 0 {
+                output.extend_from_slice(separator.as_bytes());
+            }
+            let digits = digits_from_bytes(&[*b], radix);
+            output.extend_from_slice(format!("{prefix}{digits:0>width$}").as_bytes());
+        }
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let text = std::str::from_utf8(data)?;
+        let mut decoded = if self.bigint {
+            self.decode_bigint(text)?
+        } else {
+            self.decode_list(text)?
+        };
+        output.append(&mut decoded);
+        Ok(())
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let mut codec = Self::default();
+        for arg in args.split(',').filter(|a| !a.is_empty()) {
+            match arg.split_once('=') {
+                None if arg == "bigint" => codec.bigint = true,
+                None if arg == "prefix" => codec.prefix = true,
+                None => codec.radix = Some(arg.parse().ok().filter(|r| (2..=36).contains(r))?),
+                Some(("sep", separator)) => codec.separator = Some(parse_separator(separator)),
+                _ => return None,
+            }
+        }
+        Some(Box::new(codec) as super::Plugin)
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        // At least two numbers, otherwise it is a job for other codecs
+        let Ok(text) = std::str::from_utf8(data) else {
+            return false;
+        };
+        let Ok(numbers) = self.split_numbers(text) else {
+            return false;
+        };
+        numbers.len() >= 2
+            && numbers.iter().all(|n| {
+                strip_prefix(n, self.radix).is_ok_and(|(_, digits)| {
+                    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit())
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codec(args: &str) -> crate::codecs::Plugin {
+        RadixCodec::default().build(args).unwrap()
+    }
+
+    #[test]
+    fn guess_radix() {
+        let codec = RadixCodec::default();
+        for input in [
+            "01001000 01101001",
+            "110 151",
+            "72,105",
+            "0x48 0x69",
+            "[72, 105]\n",
+        ] {
+            assert!(codec.detect(input.as_bytes()));
+            assert_eq!(codec.decode(input.as_bytes()).unwrap(), b"Hi", "{input}");
+        }
+        assert_eq!(codec.decode(b"104 101 108 108 111").unwrap(), b"hello");
+        assert!(codec.decode(b"999 1").is_err());
+        assert!(!codec.detect(b"Hi"));
+    }
+
+    #[test]
+    fn separators() {
+        assert_eq!(codec("2").encode(b"Hi").unwrap(), b"01001000 01101001");
+        assert_eq!(
+            codec("16,sep=comma,prefix").encode(b"Hi").unwrap(),
+            b"0x48,0x69"
+        );
+        assert_eq!(codec("sep=none").encode(b"Hi\n").unwrap(), b"072105010");
+        assert_eq!(codec("sep=none").decode(b"072105010").unwrap(), b"Hi\n");
+        assert_eq!(
+            codec("2,sep=none").decode(b"0100100001101001").unwrap(),
+            b"Hi"
+        );
+        assert_eq!(codec("8,sep=-").decode(b"110 - 151").unwrap(), b"Hi");
+        assert!(matches!(
+            codec("sep=none").decode("072é105".as_bytes()),
+            Err(CodecError::NonAsciiChar(0xc3))
+        ));
+    }
+
+    #[test]
+    fn bigint() {
+        let codec = codec("bigint");
+        let number = b"5735816763073854953388147237921";
+        assert_eq!(codec.encode(b"Hello, world!").unwrap(), number);
+        assert_eq!(codec.decode(number).unwrap(), b"Hello, world!");
+        assert_eq!(codec.decode(b"0x48656c6c6f").unwrap(), b"Hello");
+        assert_eq!(
+            self::codec("bigint,16,prefix").encode(b"\0Hello").unwrap(),
+            b"0x48656c6c6f"
+        );
+        assert_eq!(codec.encode(b"").unwrap(), b"0");
+        assert_eq!(
+            self::codec("bigint,16").decode(b"0b12").unwrap(),
+            b"\x0b\x12"
+        );
+        assert!(codec.decode(b"0x").is_err());
+    }
+
+    #[test]
+    fn prefix_matches_radix() {
+        assert_eq!(codec("16").decode(b"0b 0c").unwrap(), b"\x0b\x0c");
+        assert_eq!(codec("16").decode(b"0x0b 0B").unwrap(), b"\x0b\x0b");
+        assert_eq!(codec("2").decode(b"0b1 0b10").unwrap(), b"\x01\x02");
+        assert!(codec("16").decode(b"0x 0c").is_err());
+        assert!(RadixCodec::default().decode(b"0b 0c").is_err());
+    }
+}
@@ -1,25 +1,69 @@
-use crate::codecs::{Codec, Result};
+use crate::codecs::{
+    score::{english_score, reads_better},
+    stream::{BufferedReader, ByteMap, TransformReader},
+    Codec, Result, Stream,
+};
 
 const DEFAULT_KEY: u8 = 13;
 
+/// First and last characters rotated by ROT47
+const ROT47_RANGE: (u8, u8) = (b'!', b'~');
+
 #[derive(Clone)]
-pub struct RotCodec(u8);
+pub struct RotCodec {
+    /// Rotation of letters
+    key: u8,
+
+    /// Rotation of digits
+    digits: u8,
+
+    /// Output all rotations, from the most to the least English-like
+    all: bool,
+}
 
 impl Default for RotCodec {
     fn default() -> Self {
-        Self(DEFAULT_KEY)
+        Self {
+            key: DEFAULT_KEY,
+            digits: DEFAULT_KEY % 10,
+            all: false,
+        }
     }
 }
 
 impl RotCodec {
-    fn apply(key: u8, b: u8) -> u8 {
+    fn apply(key: u8, digits: u8, b: u8) -> u8 {
         match b {
             b'a'..=b'z' => ((b - b'a' + key) % 26) + b'a',
             b'A'..=b'Z' => ((b - b'A' + key) % 26) + b'A',
-            b'0'..=b'9' => ((b - b'0' + key) % 10) + b'0',
+            b'0'..=b'9' => ((b - b'0' + digits) % 10) + b'0',
             _ => b,
         }
     }
+
+    /// Keys undoing the rotation
+    fn inverse(&self) -> (u8, u8) {
+        ((26 - self.key) % 26, (10 - self.digits) % 10)
+    }
+
+    fn decode_all(data: &[u8], output: &mut Vec<u8>) {
+        // Rotations are labelled with the key to decode them with
+        let mut rotations: Vec<_> = (1..26)
+            .map(|key| {
+                let rotated: Vec<u8> = data.iter().map(|b| Self::apply(26 - key, 0, *b)).collect();
+                (key, english_score(&rotated[..]), rotated)
+            })
+            .collect();
+        rotations.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        for (key, _, rotated) in rotations {
+            output.extend_from_slice(format!("{key:2}: ").as_bytes());
+            output.extend_from_slice(&rotated[..]);
+            if !rotated.ends_with(b"\n") {
+                output.push(b'\n');
+            }
+        }
+    }
 }
 
 impl Codec for RotCodec {
@@ -28,28 +72,162 @@ impl Codec for RotCodec {
     }
 
     fn description(&self) -> &'static str {
-        "rotate ascii letters"
+        "rotate ascii letters, args: KEY (default 13), digits=N, all"
     }
 
     fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
         for b in data {
-            output.push(Self::apply(self.0, *b));
+            output.push(Self::apply(self.key, self.digits, *b));
         }
 
         Ok(())
     }
 
     fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-        let key = 26 - self.0;
+        if self.all {
+            Self::decode_all(data, output);
+            return Ok(());
+        }
+
+        let (key, digits) = self.inverse();
         for b in data {
-            output.push(Self::apply(key, *b));
+            output.push(Self::apply(key, digits, *b));
         }
 
         Ok(())
     }
 
     fn build(&self, args: &str) -> Option<super::Plugin> {
-        let key: u8 = args.parse().unwrap_or(DEFAULT_KEY);
-        Some(Box::new(Self(key)) as super::Plugin)
+        let mut codec = Self::default();
+        let mut digits = None;
+        for arg in args.split(',').filter(|a| !a.is_empty()) {
+            match arg.split_once('=') {
+                None if arg == "all" => codec.all = true,
+                None => codec.key = arg.parse().ok().filter(|k| *k < 26)?,
+                Some(("digits", n)) => digits = Some(n.parse().ok().filter(|n| *n < 10)?),
+                _ => return None,
+            }
+        }
+        // Digits follow the letters unless told otherwise
+        codec.digits = digits.unwrap_or(codec.key % 10);
+        Some(Box::new(codec) as super::Plugin)
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let (key, digits) = self.inverse();
+        let rotated: Vec<u8> = data.iter().map(|b| Self::apply(key, digits, *b)).collect();
+        reads_better(data, &rotated[..])
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        let (key, digits) = (self.key, self.digits);
+        Box::new(TransformReader::new(
+            reader,
+            ByteMap(move |b| Self::apply(key, digits, b)),
+        ))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        if self.all {
+            return Box::new(BufferedReader::new(reader, move |data| self.decode(data)));
+        }
+
+        let (key, digits) = self.inverse();
+        Box::new(TransformReader::new(
+            reader,
+            ByteMap(move |b| Self::apply(key, digits, b)),
+        ))
+    }
+}
+
+/// ROT47, rotates all printable ASCII characters but space
+#[derive(Default, Clone)]
+pub struct Rot47Codec;
+
+impl Rot47Codec {
+    fn apply(b: u8) -> u8 {
+        let (first, last) = ROT47_RANGE;
+        if (first..=last).contains(&b) {
+            first + (b - first + 47) % (last - first + 1)
+        } else {
+            b
+        }
+    }
+}
+
+impl Codec for Rot47Codec {
+    fn name(&self) -> &'static str {
+        "rot47"
+    }
+
+    fn description(&self) -> &'static str {
+        "rotate printable ascii characters by 47"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        output.extend(data.iter().copied().map(Self::apply));
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        // ROT47 is its own inverse
+        self.encode_into(data, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let _ = args;
+        Some(Box::new(Self) as super::Plugin)
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let rotated: Vec<u8> = data.iter().copied().map(Self::apply).collect();
+        reads_better(data, &rotated[..])
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, ByteMap(Self::apply)))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, ByteMap(Self::apply)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digits() {
+        let codec = RotCodec::default();
+        assert_eq!(codec.encode(b"Hello 2024").unwrap(), b"Uryyb 5357");
+        assert_eq!(codec.decode(b"Uryyb 5357").unwrap(), b"Hello 2024");
+
+        let codec = RotCodec {
+            key: 13,
+            digits: 5,
+            all: false,
+        };
+        assert_eq!(codec.encode(b"Hello 2024").unwrap(), b"Uryyb 7579");
+        assert_eq!(codec.decode(b"Uryyb 7579").unwrap(), b"Hello 2024");
+    }
+
+    #[test]
+    fn all() {
+        let codec = RotCodec {
+            all: true,
+            ..Default::default()
+        };
+        let decoded = codec.decode(b"Wkh txlfn eurzq ira").unwrap();
+        assert_eq!(decoded.split(|b| *b == b'\n').count(), 26);
+        assert!(decoded.starts_with(b" 3: The quick brown fox\n"));
+    }
+
+    #[test]
+    fn rot47() {
+        let encoded = Rot47Codec.encode(b"Hello, World!").unwrap();
+        assert_eq!(encoded, b"w6==@[ (@C=5P");
+        assert_eq!(Rot47Codec.decode(&encoded[..]).unwrap(), b"Hello, World!");
+        assert!(Rot47Codec.detect(&encoded[..]));
     }
 }
@@ -0,0 +1,203 @@
+/// Score of typical English text
+const ENGLISH_TEXT_SCORE: f64 = 3.;
+
+/// Lowest score of short English sentences
+const ENGLISH_THRESHOLD: f64 = 1.5;
+
+/// Words are seldom longer than this
+const MAX_WORD_LEN: usize = 16;
+
+/// Magic bytes of common file formats
+const MAGIC_BYTES: &[&[u8]] = &[
+    b"\x89PNG\r\n\x1a\n",
+    b"\xff\xd8\xff",
+    b"GIF87a",
+    b"GIF89a",
+    b"%PDF-",
+    b"PK\x03\x04",
+    b"\x7fELF",
+    b"MZ",
+    b"\xca\xfe\xba\xbe",
+    b"\x1f\x8b",
+    b"BZh",
+    b"\xfd7zXZ\x00",
+    b"\x28\xb5\x2f\xfd",
+    b"7z\xbc\xaf\x27\x1c",
+    b"Rar!\x1a\x07",
+    b"SQLite format 3\x00",
+    b"OggS",
+    b"RIFF",
+    b"ID3",
+    b"-----BEGIN ",
+];
+
+/// Relative frequencies of letters in English text, in percent
+const LETTER_FREQUENCIES: [f64; 26] = [
+    8.167, 1.492, 2.782, 4.253, 12.702, 2.228, 2.015, 6.094, 6.966, 0.153, 0.772, 4.025, 2.406,
+    6.749, 7.507, 1.929, 0.095, 5.987, 6.327, 9.056, 2.758, 0.978, 2.360, 0.150, 1.974, 0.074,
+];
+
+/// Most common letter pairs in English text
+const COMMON_BIGRAMS: [&[u8; 2]; 50] = [
+    b"th", b"he", b"in", b"er", b"an", b"re", b"on", b"at", b"en", b"nd", b"ti", b"es", b"or",
+    b"te", b"of", b"ed", b"is", b"it", b"al", b"ar", b"st", b"to", b"nt", b"ng", b"se", b"ha",
+    b"as", b"ou", b"io", b"le", b"ve", b"co", b"me", b"de", b"hi", b"ri", b"ro", b"ic", b"ne",
+    b"ea", b"ra", b"ce", b"li", b"ch", b"ll", b"be", b"ma", b"si", b"om", b"ur",
+];
+
+/// Bonus given to common letter pairs, a single letter frequency is not enough to tell apart
+/// short texts from their rotations
+const BIGRAM_WEIGHT: f64 = 4.;
+
+/// Log-likelihood of a byte in English text
+fn byte_weight(b: u8) -> f64 {
+    match b {
+        b'a'..=b'z' => LETTER_FREQUENCIES[usize::from(b - b'a')].ln(),
+        // Capitals are less common than their lowercase counterpart
+        b'A'..=b'Z' => (LETTER_FREQUENCIES[usize::from(b - b'A')] / 4.).ln(),
+        b' ' => 15f64.ln(),
+        b'\n' | b'.' | b',' | b'\'' | b'"' | b'-' => 1.5f64.ln(),
+        b'0'..=b'9' => 0.5f64.ln(),
+        b'\t' | b'\r' | b'!'..=b'~' => 0.2f64.ln(),
+        _ => -8.,
+    }
+}
+
+/// Tells how much `data` looks like English text, higher is better. Scores of inputs of different
+/// lengths can be compared.
+pub fn english_score(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.;
+    }
+    let weights: f64 = data.iter().copied().map(byte_weight).sum();
+    let bigrams = data
+        .windows(2)
+        .filter(|w| {
+            COMMON_BIGRAMS.contains(&&[w[0].to_ascii_lowercase(), w[1].to_ascii_lowercase()])
+        })
+        .count();
+    (weights + BIGRAM_WEIGHT * bigrams as f64) / data.len() as f64
+}
+
+/// Tells if `data` reads like English text, with words separated by spaces
+pub fn looks_like_english(data: &[u8]) -> bool {
+    let spaces = data.iter().filter(|b| b.is_ascii_whitespace()).count();
+    english_score(data) >= ENGLISH_THRESHOLD
+        && (data.len() <= MAX_WORD_LEN || spaces * MAX_WORD_LEN >= data.len())
+}
+
+/// Tells if `transformed` looks like English text, when `data` did not already
+pub fn reads_better(data: &[u8], transformed: &[u8]) -> bool {
+    looks_like_english(transformed) && english_score(transformed) > english_score(data)
+}
+
+/// Counts the occurrences of each letter, ignoring case
+pub fn letter_counts(data: &[u8]) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for b in data.iter().filter(|b| b.is_ascii_alphabetic()) {
+        counts[usize::from(b.to_ascii_lowercase() - b'a')] += 1;
+    }
+    counts
+}
+
+/// Probability that two letters picked at random are the same, about 0.066 for English text and
+/// 0.038 for random letters
+pub fn index_of_coincidence(counts: &[usize; 26]) -> f64 {
+    let total: usize = counts.iter().sum();
+    if total < 2 {
+        return 0.;
+    }
+    let same: usize = counts.iter().map(|n| n * n.saturating_sub(1)).sum();
+    same as f64 / (total * (total - 1)) as f64
+}
+
+/// Chi-squared statistic of letter counts against English frequencies, lower is closer
+pub fn chi_squared(counts: &[usize; 26]) -> f64 {
+    let total: usize = counts.iter().sum();
+    counts
+        .iter()
+        .zip(LETTER_FREQUENCIES)
+        .map(|(n, frequency)| {
+            let expected = total as f64 * frequency / 100.;
+            (*n as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Tells if `data` starts like a known file format
+pub fn has_magic(data: &[u8]) -> bool {
+    MAGIC_BYTES.iter().any(|magic| data.starts_with(magic))
+}
+
+/// Ratio of printable characters, non-ASCII ones are counted as printable in valid UTF-8
+pub fn printable_ratio(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.;
+    }
+    let utf8 = std::str::from_utf8(data).is_ok();
+    let printable = data
+        .iter()
+        .filter(|b| b.is_ascii_graphic() || b.is_ascii_whitespace() || (utf8 && !b.is_ascii()))
+        .count();
+    printable as f64 / data.len() as f64
+}
+
+/// Shannon entropy, in bits per byte
+pub fn entropy(data: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for b in data {
+        counts[usize::from(*b)] += 1;
+    }
+    counts
+        .iter()
+        .filter(|c| **c > 0)
+        .map(|c| {
+            let p = *c as f64 / data.len() as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Tells how plausible it is that `output` is the actual decoding of `input`, higher is better
+pub fn plausibility(input: &[u8], output: &[u8]) -> f64 {
+    if output.is_empty() {
+        return 0.;
+    }
+
+    let mut score = printable_ratio(output)
+        + (english_score(output) / ENGLISH_TEXT_SCORE).clamp(0., 1.)
+        + (entropy(input) - entropy(output)) / 8.
+        // Text encodings make data larger, so it usually shrinks when decoded
+        + (1. - output.len() as f64 / input.len().max(1) as f64).max(0.) / 2.;
+    if std::str::from_utf8(output).is_ok() {
+        score += 1.;
+    }
+    if has_magic(output) {
+        score += 2.;
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_is_better() {
+        let text = english_score(b"The quick brown fox jumps over the lazy dog");
+        assert!(text > english_score(b"Gur dhvpx oebja sbk whzcf bire gur ynml qbt"));
+        assert!(text > english_score(b"\x16*'b3723)b 0-5,b$-:b(7/22b-4'0b6*'b.#8;b&-%"));
+        assert!(text > english_score(b"4f2c9a01e7d3b85c"));
+    }
+
+    #[test]
+    fn plausible_decodings() {
+        let input = b"SGVsbG8sIFdvcmxkIQ==";
+        assert!(plausibility(input, b"Hello, World!") > plausibility(input, b"\x1d\xe9\x8a\x6e"));
+        assert!(
+            plausibility(input, b"\x1f\x8b\x08\x00\xb5\x13") > plausibility(input, b"\x1e\x8b\x08")
+        );
+        assert_eq!(entropy(b"aaaa"), 0.);
+        assert_eq!(entropy(b"abcd"), 2.);
+    }
+}
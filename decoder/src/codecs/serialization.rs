@@ -0,0 +1,921 @@
+use serde_json::{json, Map, Number, Value};
+
+use crate::codecs::{base64::Base64StandardCodec, Codec, CodecError, Result};
+
+/// Nesting limit, so that crafted inputs cannot overflow the stack
+const MAX_DEPTH: usize = 128;
+
+/// BSON element types
+const BSON_DOUBLE: u8 = 0x01;
+const BSON_STRING: u8 = 0x02;
+const BSON_DOCUMENT: u8 = 0x03;
+const BSON_ARRAY: u8 = 0x04;
+const BSON_BINARY: u8 = 0x05;
+const BSON_UNDEFINED: u8 = 0x06;
+const BSON_OBJECT_ID: u8 = 0x07;
+const BSON_BOOLEAN: u8 = 0x08;
+const BSON_DATE: u8 = 0x09;
+const BSON_NULL: u8 = 0x0a;
+const BSON_REGEX: u8 = 0x0b;
+const BSON_CODE: u8 = 0x0d;
+const BSON_SYMBOL: u8 = 0x0e;
+const BSON_INT32: u8 = 0x10;
+const BSON_TIMESTAMP: u8 = 0x11;
+const BSON_INT64: u8 = 0x12;
+const BSON_DECIMAL128: u8 = 0x13;
+const BSON_MIN_KEY: u8 = 0xff;
+const BSON_MAX_KEY: u8 = 0x7f;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    MessagePack,
+    Cbor,
+    Bson,
+}
+
+fn float_value(value: f64) -> Value {
+    // JSON has no NaN nor infinities
+    Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+/// Byte strings have no JSON counterpart, they are written as `{"$binary": BASE64}`
+fn binary_value(bytes: &[u8]) -> Result<Value> {
+    let encoded = Base64StandardCodec::default().encode(bytes)?;
+    Ok(json!({ "$binary": String::from_utf8_lossy(&encoded[..]) }))
+}
+
+/// Bytes and BSON subtype of a `{"$binary": BASE64}` object
+fn parse_binary(map: &Map<String, Value>) -> Option<(Vec<u8>, u8)> {
+    let subtype = match map.get("$subtype") {
+        Some(subtype) => u8::try_from(subtype.as_u64()?).ok()?,
+        None => 0,
+    };
+    let fields = if map.contains_key("$subtype") { 2 } else { 1 };
+    let encoded = map.get("$binary")?.as_str()?;
+    if map.len() != fields {
+        return None;
+    }
+    let bytes = Base64StandardCodec::default()
+        .decode(encoded.as_bytes())
+        .ok()?;
+    Some((bytes, subtype))
+}
+
+/// Map keys may be any value in MessagePack and CBOR
+fn key_string(key: Value) -> String {
+    match key {
+        Value::String(key) => key,
+        key => key.to_string(),
+    }
+}
+
+fn f16_to_f64(bits: u16) -> f64 {
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f64::from(bits & 0x3ff);
+    let value = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        0x1f if mantissa == 0.0 => f64::INFINITY,
+        0x1f => f64::NAN,
+        _ => (1024.0 + mantissa) * 2f64.powi(exponent - 25),
+    };
+    if bits & 0x8000 != 0 {
+        -value
+    } else {
+        value
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.remaining() {
+            return Err(CodecError::Truncated);
+        }
+        self.pos += len;
+        Ok(&self.data[self.pos - len..self.pos])
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.read_bytes(N)?.try_into().unwrap())
+    }
+
+    /// Big endian unsigned integer of `size` bytes
+    fn read_uint(&mut self, size: usize) -> Result<u64> {
+        let bytes = self.read_bytes(size)?;
+        Ok(bytes.iter().fold(0, |acc, b| (acc << 8) | u64::from(*b)))
+    }
+
+    fn read_len(&mut self, size: usize) -> Result<usize> {
+        usize::try_from(self.read_uint(size)?).map_err(|_| CodecError::Malformed("Length overflow"))
+    }
+
+    fn read_string(&mut self, len: usize) -> Result<Value> {
+        Ok(Value::String(
+            std::str::from_utf8(self.read_bytes(len)?)?.to_owned(),
+        ))
+    }
+
+    fn check_depth(depth: usize) -> Result<()> {
+        if depth > MAX_DEPTH {
+            return Err(CodecError::Malformed("Values are nested too deeply"));
+        }
+        Ok(())
+    }
+
+    fn read_msgpack(&mut self, depth: usize) -> Result<Value> {
+        Self::check_depth(depth)?;
+        let b = self.read_u8()?;
+        let value = match b {
+            0x00..=0x7f => Value::from(b),
+            0x80..=0x8f => self.read_msgpack_map(usize::from(b & 0x0f), depth)?,
+            0x90..=0x9f => self.read_msgpack_array(usize::from(b & 0x0f), depth)?,
+            0xa0..=0xbf => self.read_string(usize::from(b & 0x1f))?,
+            0xc0 => Value::Null,
+            0xc1 => return Err(CodecError::Malformed("Reserved MessagePack marker")),
+            0xc2 => Value::Bool(false),
+            0xc3 => Value::Bool(true),
+            0xc4..=0xc6 => {
+                let len = self.read_len(1 << (b - 0xc4))?;
+                binary_value(self.read_bytes(len)?)?
+            }
+            0xc7..=0xc9 => {
+                let len = self.read_len(1 << (b - 0xc7))?;
+                self.read_msgpack_ext(len)?
+            }
+            0xca => float_value(f64::from(f32::from_be_bytes(self.read_array()?))),
+            0xcb => float_value(f64::from_be_bytes(self.read_array()?)),
+            0xcc..=0xcf => Value::from(self.read_uint(1 << (b - 0xcc))?),
+            0xd0 => Value::from(i8::from_be_bytes(self.read_array()?)),
+            0xd1 => Value::from(i16::from_be_bytes(self.read_array()?)),
+            0xd2 => Value::from(i32::from_be_bytes(self.read_array()?)),
+            0xd3 => Value::from(i64::from_be_bytes(self.read_array()?)),
+            0xd4..=0xd8 => self.read_msgpack_ext(1 << (b - 0xd4))?,
+            0xd9..=0xdb => {
+                let len = self.read_len(1 << (b - 0xd9))?;
+                self.read_string(len)?
+            }
+            0xdc | 0xdd => {
+                let len = self.read_len(2 << (b - 0xdc))?;
+                self.read_msgpack_array(len, depth)?
+            }
+            0xde | 0xdf => {
+                let len = self.read_len(2 << (b - 0xde))?;
+                self.read_msgpack_map(len, depth)?
+            }
+            0xe0..=0xff => Value::from(b as i8),
+        };
+        Ok(value)
+    }
+
+    fn read_msgpack_array(&mut self, len: usize, depth: usize) -> Result<Value> {
+        let mut items = Vec::with_capacity(len.min(self.remaining()));
+        for _ in 0..len {
+            items.push(self.read_msgpack(depth + 1)?);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn read_msgpack_map(&mut self, len: usize, depth: usize) -> Result<Value> {
+        let mut map = Map::new();
+        for _ in 0..len {
+            let key = key_string(self.read_msgpack(depth + 1)?);
+            map.insert(key, self.read_msgpack(depth + 1)?);
+        }
+        Ok(Value::Object(map))
+    }
+
+    /// Extensions are written as `{"$ext": TYPE, "data": BASE64}`
+    fn read_msgpack_ext(&mut self, len: usize) -> Result<Value> {
+        let kind = self.read_u8()? as i8;
+        let data = binary_value(self.read_bytes(len)?)?;
+        Ok(json!({ "$ext": kind, "data": data["$binary"] }))
+    }
+
+    /// Argument of a CBOR head, `None` for indefinite lengths
+    fn read_cbor_argument(&mut self, info: u8) -> Result<Option<u64>> {
+        match info {
+            0..=23 => Ok(Some(u64::from(info))),
+            24..=27 => Ok(Some(self.read_uint(1 << (info - 24))?)),
+            31 => Ok(None),
+            _ => Err(CodecError::Malformed(
+                "Reserved CBOR additional information",
+            )),
+        }
+    }
+
+    /// Consumes the break ending an indefinite length item
+    fn at_cbor_break(&mut self) -> Result<bool> {
+        match self.data.get(self.pos) {
+            Some(0xff) => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+            None => Err(CodecError::Truncated),
+        }
+    }
+
+    fn cbor_len(argument: u64) -> Result<usize> {
+        usize::try_from(argument).map_err(|_| CodecError::Malformed("Length overflow"))
+    }
+
+    /// Byte or text string, indefinite ones are made of definite chunks of the same type
+    fn read_cbor_bytes(&mut self, major: u8, argument: Option<u64>) -> Result<Vec<u8>> {
+        if let Some(len) = argument {
+            return Ok(self.read_bytes(Self::cbor_len(len)?)?.to_vec());
+        }
+        let mut bytes = Vec::new();
+        while !self.at_cbor_break()? {
+            let b = self.read_u8()?;
+            let len = match self.read_cbor_argument(b & 0x1f)? {
+                Some(len) if b >> 5 == major => len,
+                _ => return Err(CodecError::Malformed("Invalid chunk in CBOR string")),
+            };
+            bytes.extend_from_slice(self.read_bytes(Self::cbor_len(len)?)?);
+        }
+        Ok(bytes)
+    }
+
+    fn read_cbor(&mut self, depth: usize) -> Result<Value> {
+        Self::check_depth(depth)?;
+        let b = self.read_u8()?;
+        let (major, info) = (b >> 5, b & 0x1f);
+        if major == 7 {
+            let value = match info {
+                20 => Value::Bool(false),
+                21 => Value::Bool(true),
+                // Null, undefined and unassigned simple values
+                0..=19 | 22 | 23 => Value::Null,
+                24 => {
+                    self.read_u8()?;
+                    Value::Null
+                }
+                25 => float_value(f16_to_f64(u16::from_be_bytes(self.read_array()?))),
+                26 => float_value(f64::from(f32::from_be_bytes(self.read_array()?))),
+                27 => float_value(f64::from_be_bytes(self.read_array()?)),
+                _ => return Err(CodecError::Malformed("Unexpected CBOR break")),
+            };
+            return Ok(value);
+        }
+
+        let argument = self.read_cbor_argument(info)?;
+        let value = match (major, argument) {
+            (0, Some(n)) => Value::from(n),
+            (1, Some(n)) => match i64::try_from(n) {
+                Ok(n) => Value::from(-1 - n),
+                Err(_) => float_value(-1.0 - n as f64),
+            },
+            (2, _) => binary_value(&self.read_cbor_bytes(major, argument)?[..])?,
+            (3, _) => Value::String(
+                String::from_utf8(self.read_cbor_bytes(major, argument)?)
+                    .map_err(|e| e.utf8_error())?,
+            ),
+            (4, _) => {
+                let mut items = Vec::new();
+                while match argument {
+                    Some(len) => (items.len() as u64) < len,
+                    None => !self.at_cbor_break()?,
+                } {
+                    items.push(self.read_cbor(depth + 1)?);
+                }
+                Value::Array(items)
+            }
+            (5, _) => {
+                let mut map = Map::new();
+                let mut count = 0;
+                while match argument {
+                    Some(len) => count < len,
+                    None => !self.at_cbor_break()?,
+                } {
+                    let key = key_string(self.read_cbor(depth + 1)?);
+                    map.insert(key, self.read_cbor(depth + 1)?);
+                    count += 1;
+                }
+                Value::Object(map)
+            }
+            // Tags are written as `{"$tag": TAG, "value": VALUE}`
+            (6, Some(tag)) => json!({ "$tag": tag, "value": self.read_cbor(depth + 1)? }),
+            _ => return Err(CodecError::Malformed("Invalid CBOR indefinite length")),
+        };
+        Ok(value)
+    }
+
+    fn read_i32_le(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_cstring(&mut self) -> Result<String> {
+        let len = self.data[self.pos..]
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or(CodecError::Truncated)?;
+        let text = std::str::from_utf8(self.read_bytes(len)?)?.to_owned();
+        self.pos += 1;
+        Ok(text)
+    }
+
+    fn read_bson_string(&mut self) -> Result<String> {
+        let len = usize::try_from(self.read_i32_le()?)
+            .ok()
+            .filter(|len| *len > 0)
+            .ok_or(CodecError::Malformed("Invalid BSON string length"))?;
+        let bytes = self.read_bytes(len)?;
+        let (text, nul) = bytes.split_at(len - 1);
+        if nul != [0] {
+            return Err(CodecError::Malformed("BSON string is not NUL terminated"));
+        }
+        Ok(std::str::from_utf8(text)?.to_owned())
+    }
+
+    /// Documents and arrays, which are documents with keys `0`, `1`...
+    fn read_bson_document(&mut self, depth: usize, is_array: bool) -> Result<Value> {
+        Self::check_depth(depth)?;
+        let start = self.pos;
+        let len = usize::try_from(self.read_i32_le()?)
+            .ok()
+            .filter(|len| *len >= 5)
+            .ok_or(CodecError::Malformed("Invalid BSON document length"))?;
+        if len - 4 > self.remaining() {
+            return Err(CodecError::Truncated);
+        }
+
+        let mut map = Map::new();
+        let mut items = Vec::new();
+        loop {
+            let kind = self.read_u8()?;
+            if kind == 0 {
+                break;
+            }
+            let name = self.read_cstring()?;
+            let value = match kind {
+                BSON_DOUBLE => float_value(f64::from_le_bytes(self.read_array()?)),
+                BSON_STRING => Value::String(self.read_bson_string()?),
+                BSON_DOCUMENT => self.read_bson_document(depth + 1, false)?,
+                BSON_ARRAY => self.read_bson_document(depth + 1, true)?,
+                BSON_BINARY => {
+                    let len = usize::try_from(self.read_i32_le()?)
+                        .map_err(|_| CodecError::Malformed("Invalid BSON binary length"))?;
+                    let subtype = self.read_u8()?;
+                    let mut value = binary_value(self.read_bytes(len)?)?;
+                    if subtype != 0 {
+                        value["$subtype"] = subtype.into();
+                    }
+                    value
+                }
+                BSON_UNDEFINED | BSON_NULL => Value::Null,
+                BSON_OBJECT_ID => {
+                    let id: String = self
+                        .read_bytes(12)?
+                        .iter()
+                        .map(|b| format!("{b:02x}"))
+                        .collect();
+                    json!({ "$oid": id })
+                }
+                BSON_BOOLEAN => Value::Bool(self.read_u8()? != 0),
+                BSON_DATE => json!({ "$date": i64::from_le_bytes(self.read_array()?) }),
+                BSON_REGEX => {
+                    json!({ "$regex": self.read_cstring()?, "$options": self.read_cstring()? })
+                }
+                BSON_CODE => json!({ "$code": self.read_bson_string()? }),
+                BSON_SYMBOL => Value::String(self.read_bson_string()?),
+                BSON_INT32 => Value::from(self.read_i32_le()?),
+                BSON_TIMESTAMP => {
+                    let increment = u32::from_le_bytes(self.read_array()?);
+                    let time = u32::from_le_bytes(self.read_array()?);
+                    json!({ "$timestamp": { "t": time, "i": increment } })
+                }
+                BSON_INT64 => Value::from(i64::from_le_bytes(self.read_array()?)),
+                BSON_DECIMAL128 => {
+                    let bits: String = self
+                        .read_bytes(16)?
+                        .iter()
+                        .rev()
+                        .map(|b| format!("{b:02x}"))
+                        .collect();
+                    json!({ "$decimal128": bits })
+                }
+                BSON_MIN_KEY => json!({ "$minKey": 1 }),
+                BSON_MAX_KEY => json!({ "$maxKey": 1 }),
+                _ => return Err(CodecError::Malformed("Unsupported BSON element type")),
+            };
+            if is_array {
+                items.push(value);
+            } else {
+                map.insert(name, value);
+            }
+        }
+
+        if self.pos != start + len {
+            return Err(CodecError::Malformed("BSON document length mismatch"));
+        }
+        Ok(if is_array {
+            Value::Array(items)
+        } else {
+            Value::Object(map)
+        })
+    }
+}
+
+/// Writes a length with the smallest of the fix, 8, 16 and 32 bits forms a type has
+fn write_msgpack_len(
+    output: &mut Vec<u8>,
+    len: usize,
+    fix: Option<(u8, usize)>,
+    markers: [Option<u8>; 3],
+) -> Result<()> {
+    match (fix, markers) {
+        (Some((marker, max)), _) if len <= max => output.push(marker | len as u8),
+        (_, [Some(marker), _, _]) if len <= 0xff => output.extend([marker, len as u8]),
+        (_, [_, Some(marker), _]) if len <= 0xffff => {
+            output.push(marker);
+            output.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        (_, [_, _, Some(marker)]) if u32::try_from(len).is_ok() => {
+            output.push(marker);
+            output.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+        _ => return Err(CodecError::Malformed("Value is too long for MessagePack")),
+    }
+    Ok(())
+}
+
+fn write_msgpack(value: &Value, output: &mut Vec<u8>) -> Result<()> {
+    match value {
+        Value::Null => output.push(0xc0),
+        Value::Bool(b) => output.push(if *b { 0xc3 } else { 0xc2 }),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(n), _) if n <= 0x7f => output.push(n as u8),
+            (Some(n), _) if n <= 0xff => output.extend([0xcc, n as u8]),
+            (Some(n), _) if n <= 0xffff => {
+                output.push(0xcd);
+                output.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            (Some(n), _) if n <= 0xffff_ffff => {
+                output.push(0xce);
+                output.extend_from_slice(&(n as u32).to_be_bytes());
+            }
+            (Some(n), _) => {
+                output.push(0xcf);
+                output.extend_from_slice(&n.to_be_bytes());
+            }
+            (_, Some(n)) if n >= -32 => output.push(n as u8),
+            (_, Some(n)) if n >= i64::from(i8::MIN) => output.extend([0xd0, n as u8]),
+            (_, Some(n)) if n >= i64::from(i16::MIN) => {
+                output.push(0xd1);
+                output.extend_from_slice(&(n as i16).to_be_bytes());
+            }
+            (_, Some(n)) if n >= i64::from(i32::MIN) => {
+                output.push(0xd2);
+                output.extend_from_slice(&(n as i32).to_be_bytes());
+            }
+            (_, Some(n)) => {
+                output.push(0xd3);
+                output.extend_from_slice(&n.to_be_bytes());
+            }
+            _ => {
+                output.push(0xcb);
+                output.extend_from_slice(&n.as_f64().unwrap_or(f64::NAN).to_be_bytes());
+            }
+        },
+        Value::String(s) => {
+            write_msgpack_len(
+                output,
+                s.len(),
+                Some((0xa0, 31)),
+                [Some(0xd9), Some(0xda), Some(0xdb)],
+            )?;
+            output.extend_from_slice(s.as_bytes());
+        }
+        Value::Array(items) => {
+            write_msgpack_len(
+                output,
+                items.len(),
+                Some((0x90, 15)),
+                [None, Some(0xdc), Some(0xdd)],
+            )?;
+            for item in items {
+                write_msgpack(item, output)?;
+            }
+        }
+        Value::Object(map) => {
+            if let Some((bytes, _)) = parse_binary(map) {
+                write_msgpack_len(
+                    output,
+                    bytes.len(),
+                    None,
+                    [Some(0xc4), Some(0xc5), Some(0xc6)],
+                )?;
+                output.extend_from_slice(&bytes[..]);
+                return Ok(());
+            }
+            if let Some((kind, bytes)) = parse_msgpack_ext(map) {
+                match bytes.len() {
+                    1 => output.push(0xd4),
+                    2 => output.push(0xd5),
+                    4 => output.push(0xd6),
+                    8 => output.push(0xd7),
+                    16 => output.push(0xd8),
+                    len => {
+                        write_msgpack_len(output, len, None, [Some(0xc7), Some(0xc8), Some(0xc9)])?
+                    }
+                }
+                output.push(kind as u8);
+                output.extend_from_slice(&bytes[..]);
+                return Ok(());
+            }
+
+            write_msgpack_len(
+                output,
+                map.len(),
+                Some((0x80, 15)),
+                [None, Some(0xde), Some(0xdf)],
+            )?;
+            for (key, value) in map {
+                write_msgpack(&Value::String(key.clone()), output)?;
+                write_msgpack(value, output)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Type and data of a `{"$ext": TYPE, "data": BASE64}` object
+fn parse_msgpack_ext(map: &Map<String, Value>) -> Option<(i8, Vec<u8>)> {
+    let kind = i8::try_from(map.get("$ext")?.as_i64()?).ok()?;
+    let data = map.get("data")?.as_str()?;
+    if map.len() != 2 {
+        return None;
+    }
+    let bytes = Base64StandardCodec::default()
+        .decode(data.as_bytes())
+        .ok()?;
+    Some((kind, bytes))
+}
+
+fn write_cbor_head(output: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+    match argument {
+        0..=23 => output.push(major | argument as u8),
+        24..=0xff => output.extend([major | 24, argument as u8]),
+        0x100..=0xffff => {
+            output.push(major | 25);
+            output.extend_from_slice(&(argument as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            output.push(major | 26);
+            output.extend_from_slice(&(argument as u32).to_be_bytes());
+        }
+        _ => {
+            output.push(major | 27);
+            output.extend_from_slice(&argument.to_be_bytes());
+        }
+    }
+}
+
+fn write_cbor(value: &Value, output: &mut Vec<u8>) {
+    match value {
+        Value::Null => output.push(0xf6),
+        Value::Bool(b) => output.push(if *b { 0xf5 } else { 0xf4 }),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(n), _) => write_cbor_head(output, 0, n),
+            (_, Some(n)) => write_cbor_head(output, 1, (-1 - n) as u64),
+            _ => {
+                output.push(0xfb);
+                output.extend_from_slice(&n.as_f64().unwrap_or(f64::NAN).to_be_bytes());
+            }
+        },
+        Value::String(s) => {
+            write_cbor_head(output, 3, s.len() as u64);
+            output.extend_from_slice(s.as_bytes());
+        }
+        Value::Array(items) => {
+            write_cbor_head(output, 4, items.len() as u64);
+            for item in items {
+                write_cbor(item, output);
+            }
+        }
+        Value::Object(map) => {
+            if let Some((bytes, _)) = parse_binary(map) {
+                write_cbor_head(output, 2, bytes.len() as u64);
+                output.extend_from_slice(&bytes[..]);
+            } else if let (2, Some(tag), Some(value)) = (
+                map.len(),
+                map.get("$tag").and_then(Value::as_u64),
+                map.get("value"),
+            ) {
+                write_cbor_head(output, 6, tag);
+                write_cbor(value, output);
+            } else {
+                write_cbor_head(output, 5, map.len() as u64);
+                for (key, value) in map {
+                    write_cbor_head(output, 3, key.len() as u64);
+                    output.extend_from_slice(key.as_bytes());
+                    write_cbor(value, output);
+                }
+            }
+        }
+    }
+}
+
+fn write_bson_document<'a, K: AsRef<str>>(
+    fields: impl Iterator<Item = (K, &'a Value)>,
+    output: &mut Vec<u8>,
+) -> Result<()> {
+    let start = output.len();
+    output.extend_from_slice(&[0; 4]);
+    for (key, value) in fields {
+        write_bson_element(key.as_ref(), value, output)?;
+    }
+    output.push(0);
+
+    let len = i32::try_from(output.len() - start)
+        .map_err(|_| CodecError::Malformed("Document is too long for BSON"))?;
+    output[start..start + 4].copy_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+fn write_bson_string(s: &str, output: &mut Vec<u8>) -> Result<()> {
+    let len = i32::try_from(s.len() + 1)
+        .map_err(|_| CodecError::Malformed("String is too long for BSON"))?;
+    output.extend_from_slice(&len.to_le_bytes());
+    output.extend_from_slice(s.as_bytes());
+    output.push(0);
+    Ok(())
+}
+
+/// 12 bytes of a `{"$oid": HEX}` object
+fn parse_object_id(map: &Map<String, Value>) -> Option<Vec<u8>> {
+    let id = map.get("$oid")?.as_str()?;
+    if map.len() != 1 || id.len() != 24 {
+        return None;
+    }
+    (0..id.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(id.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn write_bson_element(key: &str, value: &Value, output: &mut Vec<u8>) -> Result<()> {
+    if key.contains('\0') {
+        return Err(CodecError::Malformed("BSON keys cannot contain NUL"));
+    }
+    let kind_pos = output.len();
+    output.push(0);
+    output.extend_from_slice(key.as_bytes());
+    output.push(0);
+
+    let kind = match value {
+        Value::Null => BSON_NULL,
+        Value::Bool(b) => {
+            output.push(u8::from(*b));
+            BSON_BOOLEAN
+        }
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(n), _) if i32::try_from(n).is_ok() => {
+                output.extend_from_slice(&(n as i32).to_le_bytes());
+                BSON_INT32
+            }
+            (Some(n), _) => {
+                output.extend_from_slice(&n.to_le_bytes());
+                BSON_INT64
+            }
+            (None, Some(_)) => return Err(CodecError::Malformed("Integer does not fit in BSON")),
+            _ => {
+                output.extend_from_slice(&n.as_f64().unwrap_or(f64::NAN).to_le_bytes());
+                BSON_DOUBLE
+            }
+        },
+        Value::String(s) => {
+            write_bson_string(s, output)?;
+            BSON_STRING
+        }
+        Value::Array(items) => {
+            write_bson_document(
+                items.iter().enumerate().map(|(i, v)| (i.to_string(), v)),
+                output,
+            )?;
+            BSON_ARRAY
+        }
+        Value::Object(map) => {
+            if let Some((bytes, subtype)) = parse_binary(map) {
+                let len = i32::try_from(bytes.len())
+                    .map_err(|_| CodecError::Malformed("Binary is too long for BSON"))?;
+                output.extend_from_slice(&len.to_le_bytes());
+                output.push(subtype);
+                output.extend_from_slice(&bytes[..]);
+                BSON_BINARY
+            } else if let Some(id) = parse_object_id(map) {
+                output.extend_from_slice(&id[..]);
+                BSON_OBJECT_ID
+            } else if let (1, Some(date)) = (map.len(), map.get("$date").and_then(Value::as_i64)) {
+                output.extend_from_slice(&date.to_le_bytes());
+                BSON_DATE
+            } else {
+                write_bson_document(map.iter(), output)?;
+                BSON_DOCUMENT
+            }
+        }
+    };
+    output[kind_pos] = kind;
+    Ok(())
+}
+
+/// Binary serialization formats, decoding converts them to JSON and encoding converts JSON back.
+/// Types without a JSON counterpart are written as objects with `$` prefixed keys.
+#[derive(Clone)]
+pub struct SerializationCodec {
+    format: Format,
+}
+
+impl SerializationCodec {
+    pub const fn new(format: Format) -> Self {
+        Self { format }
+    }
+
+    /// Top level values, inputs may hold several of them back to back
+    fn read_values(&self, data: &[u8]) -> Result<Vec<Value>> {
+        if data.is_empty() {
+            return Err(CodecError::Truncated);
+        }
+        let mut reader = Reader { data, pos: 0 };
+        let mut values = Vec::new();
+        while reader.remaining() > 0 {
+            values.push(match self.format {
+                Format::MessagePack => reader.read_msgpack(0)?,
+                Format::Cbor => reader.read_cbor(0)?,
+                Format::Bson => reader.read_bson_document(0, false)?,
+            });
+        }
+        Ok(values)
+    }
+}
+
+impl Codec for SerializationCodec {
+    fn name(&self) -> &'static str {
+        match self.format {
+            Format::MessagePack => "msgpack",
+            Format::Cbor => "cbor",
+            Format::Bson => "bson",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self.format {
+            Format::MessagePack => "MessagePack to JSON, encoding converts JSON to MessagePack",
+            Format::Cbor => "CBOR to JSON, encoding converts JSON to CBOR",
+            Format::Bson => "BSON documents to JSON, encoding converts JSON objects to BSON",
+        }
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        for value in serde_json::Deserializer::from_slice(data).into_iter::<Value>() {
+            match (self.format, value?) {
+                (Format::MessagePack, value) => write_msgpack(&value, output)?,
+                (Format::Cbor, value) => write_cbor(&value, output),
+                (Format::Bson, Value::Object(map)) => write_bson_document(map.iter(), output)?,
+                (Format::Bson, _) => {
+                    return Err(CodecError::Malformed("BSON documents must be objects"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        for value in self.read_values(data)? {
+            serde_json::to_writer_pretty(&mut *output, &value)?;
+            output.push(b'\n');
+        }
+        Ok(())
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        if !args.is_empty() {
+            return None;
+        }
+        Some(Box::new(self.clone()) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        size * 4
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        // Maps, arrays and CBOR tags, anything else is too likely to parse by accident
+        let Some(first) = data.first() else {
+            return false;
+        };
+        let is_container = match self.format {
+            Format::MessagePack => matches!(first, 0x80..=0x9f | 0xdc..=0xdf),
+            Format::Cbor => matches!(first, 0x80..=0xdb),
+            Format::Bson => true,
+        };
+        is_container && self.read_values(data).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = r#"{
+  "name": "decoder",
+  "tags": [
+    1,
+    -200,
+    70000,
+    -5000000000,
+    1.5,
+    true,
+    null
+  ],
+  "nested": {
+    "bytes": {
+      "$binary": "3q2+7w=="
+    }
+  }
+}
+"#;
+
+    #[test]
+    fn msgpack() {
+        let codec = SerializationCodec::new(Format::MessagePack);
+        let encoded = codec.encode(JSON.as_bytes()).unwrap();
+        assert!(encoded.starts_with(b"\x83\xa4name\xa7decoder\xa4tags\x97\x01\xd1\xff\x38\xce"));
+        assert!(encoded.ends_with(b"\xa5bytes\xc4\x04\xde\xad\xbe\xef"));
+        assert!(codec.detect(&encoded[..]));
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), JSON.as_bytes());
+
+        let ext = codec.decode(b"\xd6\xff\x00\x00\x00\x01").unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&ext[..]).unwrap(),
+            json!({ "$ext": -1, "data": "AAAAAQ==" })
+        );
+        assert_eq!(codec.encode(&ext[..]).unwrap(), b"\xd6\xff\x00\x00\x00\x01");
+        assert!(codec.decode(b"\x92\x01").is_err());
+    }
+
+    #[test]
+    fn cbor() {
+        let codec = SerializationCodec::new(Format::Cbor);
+        let encoded = codec.encode(JSON.as_bytes()).unwrap();
+        assert!(encoded.starts_with(b"\xa3\x64name\x67decoder\x64tags\x87\x01\x38\xc7\x1a"));
+        assert!(codec.detect(&encoded[..]));
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), JSON.as_bytes());
+
+        // Tagged date, indefinite map and string, half float and undefined
+        let decoded = codec
+            .decode(b"\xc0\x74\x32\x30\x31\x33\x2d\x30\x33\x2d\x32\x31\x54\x32\x30\x3a\x30\x34\x3a\x30\x30\x5a\xbf\x61\x61\x7f\x61\x62\x61\x63\xff\xf9\x3e\x00\xf7\xff")
+            .unwrap();
+        let values: Vec<Value> = serde_json::Deserializer::from_slice(&decoded[..])
+            .into_iter()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            [
+                json!({ "$tag": 0, "value": "2013-03-21T20:04:00Z" }),
+                json!({ "a": "bc", "1.5": null })
+            ]
+        );
+        assert_eq!(
+            codec
+                .encode(br#"{"$tag": 1, "value": 1363896240}"#)
+                .unwrap(),
+            b"\xc1\x1a\x51\x4b\x67\xb0"
+        );
+    }
+
+    #[test]
+    fn bson() {
+        let codec = SerializationCodec::new(Format::Bson);
+        let encoded = codec.encode(JSON.as_bytes()).unwrap();
+        assert_eq!(&encoded[..4], &(encoded.len() as i32).to_le_bytes());
+        assert!(codec.detect(&encoded[..]));
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), JSON.as_bytes());
+
+        let document =
+            r#"{"_id": {"$oid": "507f1f77bcf86cd799439011"}, "at": {"$date": 1700000000000}}"#;
+        let encoded = codec.encode(document.as_bytes()).unwrap();
+        assert_eq!(encoded.len(), 4 + 1 + 4 + 12 + 1 + 3 + 8 + 1);
+        let decoded = codec.decode(&encoded[..]).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&decoded[..]).unwrap(),
+            serde_json::from_str::<Value>(document).unwrap()
+        );
+        assert!(codec.encode(b"[1]").is_err());
+        assert!(codec.decode(&encoded[..encoded.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+use std::io::{self, Cursor, Read};
+
+use crate::codecs::Result;
+
+/// Boxed reader used to chain codecs
+pub type Stream<'a> = Box<dyn Read + 'a>;
+
+/// Size of the chunks read from the underlying stream
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// An incremental transformation of a byte stream
+pub trait Transform {
+    /// Transforms as much of `input` as possible into `output` and returns the number of bytes
+    /// consumed. Unconsumed bytes are handed back on the next call, prefixed to the new data.
+    /// When `eof` is set, no more input will follow: a call may still stop early after writing
+    /// some output, to spread a large output over several calls, and is then repeated with the
+    /// rest of the input.
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize>;
+}
+
+/// Runs a transformation over a complete buffer
+pub fn transform_all<T: Transform>(
+    mut transform: T,
+    data: &[u8],
+    output: &mut Vec<u8>,
+) -> Result<()> {
+    let mut pos = 0;
+    loop {
+        let len = output.len();
+        pos += transform.transform(&data[pos..], output, true)?;
+        if pos == data.len() {
+            return Ok(());
+        }
+        debug_assert!(output.len() > len, "transform stalled at end of input");
+    }
+}
+
+/// Reader applying a [`Transform`] to another reader, chunk by chunk
+pub struct TransformReader<'a, T> {
+    inner: Stream<'a>,
+    transform: T,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    pos: usize,
+    eof: bool,
+    done: bool,
+}
+
+impl<'a, T: Transform> TransformReader<'a, T> {
+    pub fn new(inner: Stream<'a>, transform: T) -> Self {
+        Self {
+            inner,
+            transform,
+            input: Vec::new(),
+            output: Vec::new(),
+            pos: 0,
+            eof: false,
+            done: false,
+        }
+    }
+}
+
+impl<T: Transform> Read for TransformReader<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.output.len() {
+            if self.done {
+                return Ok(0);
+            }
+            // A transform stopping early with some output may go on without more input
+            let stopped_early = !self.output.is_empty() && !self.input.is_empty();
+            self.output.clear();
+            self.pos = 0;
+
+            if !self.eof && !stopped_early {
+                let len = self.input.len();
+                self.input.resize(len + CHUNK_SIZE, 0);
+                let n = match self.inner.read(&mut self.input[len..]) {
+                    Ok(n) => n,
+                    Err(e) => {
+                        self.input.truncate(len);
+                        return Err(e);
+                    }
+                };
+                self.input.truncate(len + n);
+                self.eof = n == 0;
+            }
+
+            let consumed = self
+                .transform
+                .transform(&self.input[..], &mut self.output, self.eof)?;
+            self.input.drain(..consumed);
+            self.done = self.eof && self.input.is_empty();
+        }
+
+        let n = buf.len().min(self.output.len() - self.pos);
+        buf[..n].copy_from_slice(&self.output[self.pos..][..n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+type BufferedFn<'a> = Box<dyn FnOnce(&[u8]) -> Result<Vec<u8>> + 'a>;
+
+enum BufferedState<'a> {
+    Pending(Stream<'a>, BufferedFn<'a>),
+    Ready(Cursor<Vec<u8>>),
+}
+
+/// Reader for codecs that need the whole input at once: it reads everything from the inner reader
+/// on first use, and then serves the result of the conversion.
+pub struct BufferedReader<'a> {
+    state: Option<BufferedState<'a>>,
+}
+
+impl<'a> BufferedReader<'a> {
+    pub fn new(inner: Stream<'a>, f: impl FnOnce(&[u8]) -> Result<Vec<u8>> + 'a) -> Self {
+        Self {
+            state: Some(BufferedState::Pending(inner, Box::new(f))),
+        }
+    }
+}
+
+impl Read for BufferedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.state.take() {
+                Some(BufferedState::Pending(mut inner, f)) => {
+                    let mut data = Vec::new();
+                    inner.read_to_end(&mut data)?;
+                    self.state = Some(BufferedState::Ready(Cursor::new(f(&data[..])?)));
+                }
+                Some(BufferedState::Ready(mut cursor)) => {
+                    let n = cursor.read(buf)?;
+                    self.state = Some(BufferedState::Ready(cursor));
+                    return Ok(n);
+                }
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+/// Reader failing on first use, for adapters that could not be created
+pub struct ErrorReader(Option<io::Error>);
+
+impl ErrorReader {
+    pub fn new(e: io::Error) -> Self {
+        Self(Some(e))
+    }
+}
+
+impl Read for ErrorReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let _ = buf;
+        match self.0.take() {
+            Some(e) => Err(e),
+            None => Ok(0),
+        }
+    }
+}
+
+/// Transformation applying a function to each byte
+pub struct ByteMap<F>(pub F);
+
+impl<F: FnMut(u8) -> u8> Transform for ByteMap<F> {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        let _ = eof;
+        output.extend(input.iter().map(|b| (self.0)(*b)));
+        Ok(input.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reader returning at most one byte per call
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match (self.0.split_first(), buf.is_empty()) {
+                (Some((b, rest)), false) => {
+                    buf[0] = *b;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    /// Sums pairs of bytes
+    struct Pairs;
+
+    impl Transform for Pairs {
+        fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+            let mut chunks = input.chunks_exact(2);
+            output.extend(chunks.by_ref().map(|c| c[0] + c[1]));
+            if eof {
+                output.extend_from_slice(chunks.remainder());
+                Ok(input.len())
+            } else {
+                Ok(input.len() - chunks.remainder().len())
+            }
+        }
+    }
+
+    #[test]
+    fn transform_reader_keeps_leftovers() {
+        let input = [1u8, 2, 3, 4, 5];
+        let mut reader = TransformReader::new(Box::new(Trickle(&input[..])), Pairs);
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, [3, 7, 5]);
+    }
+
+    #[test]
+    fn buffered_reader() {
+        let input = b"hello";
+        let mut reader = BufferedReader::new(Box::new(Trickle(&input[..])), |data| {
+            Ok(data.iter().rev().copied().collect())
+        });
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, b"olleh");
+    }
+}
@@ -1,64 +1,56 @@
-use crate::codecs::{Codec, CodecError, Result};
+use crate::codecs::{
+    stream::{transform_all, Transform, TransformReader},
+    Codec, CodecError, Result, Stream,
+};
 
 #[derive(Default, Clone)]
 pub struct UrlCodec;
 
-impl Codec for UrlCodec {
-    fn name(&self) -> &'static str {
-        "url"
-    }
+struct UrlEncoder;
 
-    fn description(&self) -> &'static str {
-        "URL % encoding/decoding"
+impl Transform for UrlEncoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        let _ = eof;
+        UrlCodec.encode_into(input, output)?;
+        Ok(input.len())
     }
+}
 
-    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-        let mut s = String::new();
-        for b in data {
-            match *b {
-                b' ' => {
-                    output.push(b'+');
-                }
-                b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'F' | b'=' | b'.' => {
-                    output.push(*b);
-                }
-                _ => {
-                    s.clear();
-                    std::fmt::write(&mut s, format_args!("%{:02x}", *b)).unwrap();
-                    debug_assert_eq!(s.len(), 3);
-                    output.extend_from_slice(s.as_bytes());
-                }
-            }
-        }
-
-        Ok(())
-    }
+#[derive(Default)]
+enum State {
+    #[default]
+    Normal,
+    FoundPercent,
+    FirstHex {
+        val: u8,
+        orig: u8,
+    },
+}
 
-    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-        enum State {
-            Normal,
-            FoundPercent,
-            FirstHex { val: u8, orig: u8 },
-        }
-        let mut state = State::Normal;
+#[derive(Default)]
+struct UrlDecoder {
+    state: State,
+}
 
-        fn get_hex_value(b: &u8) -> Option<u8> {
-            match *b {
-                b'0'..=b'9' => Some(*b - b'0'),
-                b'a'..=b'f' => Some(*b - b'A' + 10),
-                b'A'..=b'F' => Some(*b - b'F' + 10),
-                _ => None,
-            }
-        }
+fn get_hex_value(b: &u8) -> Option<u8> {
+    match *b {
+        b'0'..=b'9' => Some(*b - b'0'),
+        b'a'..=b'f' => Some(*b - b'a' + 10),
+        b'A'..=b'F' => Some(*b - b'A' + 10),
+        _ => None,
+    }
+}
 
-        for b in data {
+impl Transform for UrlDecoder {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, eof: bool) -> Result<usize> {
+        for b in input {
             if *b > 0x7f {
                 return Err(CodecError::NonAsciiChar(*b));
             }
-            match state {
+            match self.state {
                 State::Normal => {
                     if *b == b'%' {
-                        state = State::FoundPercent;
+                        self.state = State::FoundPercent;
                     } else if *b == b'+' {
                         output.push(b' ');
                     } else if b.is_ascii() {
@@ -69,11 +61,11 @@ impl Codec for UrlCodec {
                 }
                 State::FoundPercent => {
                     if let Some(val) = get_hex_value(b) {
-                        state = State::FirstHex { val, orig: *b };
+                        self.state = State::FirstHex { val, orig: *b };
                     } else {
                         output.push(b'%');
                         output.push(*b);
-                        state = State::Normal;
+                        self.state = State::Normal;
                     }
                 }
                 State::FirstHex { val, orig } => {
@@ -84,7 +76,48 @@ impl Codec for UrlCodec {
                         output.push(orig);
                         output.push(*b);
                     }
-                    state = State::Normal;
+                    self.state = State::Normal;
+                }
+            }
+        }
+
+        if eof {
+            // Incomplete escape sequences are kept as-is
+            match std::mem::take(&mut self.state) {
+                State::Normal => {}
+                State::FoundPercent => output.push(b'%'),
+                State::FirstHex { orig, .. } => output.extend_from_slice(&[b'%', orig]),
+            }
+        }
+
+        Ok(input.len())
+    }
+}
+
+impl Codec for UrlCodec {
+    fn name(&self) -> &'static str {
+        "url"
+    }
+
+    fn description(&self) -> &'static str {
+        "URL % encoding/decoding"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let mut s = String::new();
+        for b in data {
+            match *b {
+                b' ' => {
+                    output.push(b'+');
+                }
+                b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'F' | b'=' | b'.' => {
+                    output.push(*b);
+                }
+                _ => {
+                    s.clear();
+                    std::fmt::write(&mut s, format_args!("%{:02x}", *b)).unwrap();
+                    debug_assert_eq!(s.len(), 3);
+                    output.extend_from_slice(s.as_bytes());
                 }
             }
         }
@@ -92,6 +125,10 @@ impl Codec for UrlCodec {
         Ok(())
     }
 
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        transform_all(UrlDecoder::default(), data, output)
+    }
+
     fn build(&self, args: &str) -> Option<super::Plugin> {
         let _ = args;
         Some(Box::new(Self) as super::Plugin)
@@ -104,4 +141,18 @@ impl Codec for UrlCodec {
     fn encoded_size_hint(&self, size: usize) -> usize {
         size
     }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        // A `+` alone is too common to tell anything
+        data.windows(3)
+            .any(|w| w[0] == b'%' && w[1].is_ascii_hexdigit() && w[2].is_ascii_hexdigit())
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, UrlEncoder))
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        Box::new(TransformReader::new(reader, UrlDecoder::default()))
+    }
 }
@@ -0,0 +1,281 @@
+use crate::codecs::{Codec, CodecError, Result};
+
+const XX: &[u8; 64] = b"+-0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Bytes encoded per line, as written by the `uuencode` tool
+const LINE_LEN: usize = 45;
+
+const DEFAULT_MODE: u32 = 0o644;
+const DEFAULT_NAME: &str = "data";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// Characters from space to backquote, with backquote standing for zero
+    Uu,
+
+    /// Letters, digits, `+` and `-`, safe through EBCDIC gateways
+    Xx,
+}
+
+impl Variant {
+    fn symbol(&self, value: u8) -> u8 {
+        match self {
+            Self::Uu if value == 0 => b'`',
+            Self::Uu => value + b' ',
+            Self::Xx => XX[usize::from(value)],
+        }
+    }
+
+    fn value(&self, b: u8) -> Option<u8> {
+        match self {
+            Self::Uu => (b' '..=b'`').contains(&b).then(|| (b - b' ') & 0x3f),
+            Self::Xx => XX.iter().position(|s| *s == b).map(|pos| pos as u8),
+        }
+    }
+}
+
+/// Line based encodings framed by `begin MODE NAME` and `end` lines
+#[derive(Clone)]
+pub struct UuencodeCodec {
+    variant: Variant,
+
+    /// Permissions written in the header
+    mode: u32,
+
+    /// File name written in the header
+    name: String,
+}
+
+impl UuencodeCodec {
+    pub const fn new(variant: Variant) -> Self {
+        Self {
+            variant,
+            mode: DEFAULT_MODE,
+            name: String::new(),
+        }
+    }
+
+    /// Mode and name of a `begin` line
+    fn parse_header(line: &str) -> Option<(u32, &str)> {
+        let (mode, name) = line.strip_prefix("begin ")?.trim().split_once(' ')?;
+        Some((u32::from_str_radix(mode, 8).ok()?, name))
+    }
+
+    /// Decodes a line, returns `None` on the zero length line ending the data
+    fn decode_line(&self, line: &[u8], output: &mut Vec<u8>) -> Result<Option<()>> {
+        let Some((&first, symbols)) = line.split_first() else {
+            return Ok(Some(()));
+        };
+        let len = usize::from(
+            self.variant
+                .value(first)
+                .ok_or(CodecError::InvalidChar(first))?,
+        );
+        if len == 0 {
+            return Ok(None);
+        }
+
+        // Trailing spaces are often stripped from uuencoded lines, they stand for zeroes
+        let mut values = Vec::with_capacity(len.div_ceil(3) * 4);
+        for i in 0..len.div_ceil(3) * 4 {
+            let value = match symbols.get(i) {
+                Some(b) => self.variant.value(*b).ok_or(CodecError::InvalidChar(*b))?,
+                None if self.variant == Variant::Uu => 0,
+                None => return Err(CodecError::Truncated),
+            };
+            values.push(value);
+        }
+
+        let mut decoded = Vec::with_capacity(len + 2);
+        for group in values.chunks(4) {
+            let group = group.iter().fold(0u32, |acc, v| (acc << 6) | u32::from(*v));
+            decoded.extend_from_slice(&group.to_be_bytes()[1..]);
+        }
+        output.extend_from_slice(&decoded[..len]);
+        Ok(Some(()))
+    }
+
+    /// The length symbol matches the number of symbols on the line, trailing spaces of the last
+    /// group may be stripped
+    fn is_line(&self, line: &[u8]) -> bool {
+        let Some((&first, symbols)) = line.split_first() else {
+            return false;
+        };
+        let Some(len) = self.variant.value(first) else {
+            return false;
+        };
+        let expected = usize::from(len).div_ceil(3) * 4;
+        symbols.iter().all(|b| self.variant.value(*b).is_some())
+            && (symbols.len() == expected
+                || (self.variant == Variant::Uu
+                    && (expected.saturating_sub(3)..expected).contains(&symbols.len())))
+    }
+}
+
+impl Codec for UuencodeCodec {
+    fn name(&self) -> &'static str {
+        match self.variant {
+            Variant::Uu => "uuencode",
+            Variant::Xx => "xxencode",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self.variant {
+            Variant::Uu => "uuencoding with begin/end lines, args: mode=MODE (octal), name=NAME",
+            Variant::Xx => "xxencoding with begin/end lines, args: mode=MODE (octal), name=NAME",
+        }
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let name = if self.name.is_empty() {
+            DEFAULT_NAME
+        } else {
+            &self.name
+        };
+        output.extend_from_slice(format!("begin {:03o} {name}\n", self.mode).as_bytes());
+        for line in data.chunks(LINE_LEN) {
+            output.push(self.variant.symbol(line.len() as u8));
+            for group in line.chunks(3) {
+                let mut bytes = [0u8; 4];
+                bytes[1..=group.len()].copy_from_slice(group);
+                let group = u32::from_be_bytes(bytes);
+                output.extend(
+                    (0..4)
+                        .rev()
+                        .map(|i| self.variant.symbol((group >> (6 * i)) as u8 & 0x3f)),
+                );
+            }
+            output.push(b'\n');
+        }
+        output.push(self.variant.symbol(0));
+        output.extend_from_slice(b"\nend\n");
+        Ok(())
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let mut lines = data
+            .split(|b| *b == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .skip_while(|line| line.iter().all(u8::is_ascii_whitespace))
+            .peekable();
+
+        // The header is optional, bare lines are decoded as well when well formed
+        let mut framed = false;
+        if let Some(line) = lines.peek()
+            && let Ok(line) = std::str::from_utf8(line)
+            && let Some((mode, name)) = Self::parse_header(line)
+        {
+            eprintln!("File: {name}, mode: {mode:03o}");
+            lines.next();
+            framed = true;
+        }
+
+        for line in lines {
+            if line == b"end" {
+                break;
+            }
+            if !framed && !line.is_empty() && !self.is_line(line) {
+                return Err(CodecError::Malformed(
+                    "Line length does not match its symbols",
+                ));
+            }
+            if self.decode_line(line, output)?.is_none() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let mut codec = Self::new(self.variant);
+        for arg in args.split(',').filter(|a| !a.is_empty()) {
+            match arg.split_once('=')? {
+                ("mode", mode) => codec.mode = u32::from_str_radix(mode, 8).ok()?,
+                ("name", name) => codec.name = name.to_owned(),
+                _ => return None,
+            }
+        }
+        Some(Box::new(codec) as super::Plugin)
+    }
+
+    fn decoded_size_hint(&self, size: usize) -> usize {
+        (size / 4) * 3
+    }
+
+    fn encoded_size_hint(&self, size: usize) -> usize {
+        size.div_ceil(3) * 4 + size / LINE_LEN * 2 + 32
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        let mut lines = data
+            .split(|b| *b == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .filter(|line| !line.is_empty())
+            .peekable();
+        if let Some(line) = lines.peek()
+            && std::str::from_utf8(line).is_ok_and(|line| Self::parse_header(line).is_some())
+        {
+            lines.next();
+        }
+
+        // Full lines are the best hint, or a single line for short inputs
+        let body: Vec<_> = lines.take_while(|line| *line != b"end").collect();
+        body.iter()
+            .any(|line| self.variant.value(line[0]) == Some(LINE_LEN as u8) || body.len() <= 2)
+            && body.iter().all(|line| self.is_line(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uuencode() {
+        let codec = UuencodeCodec::new(Variant::Uu)
+            .build("mode=600,name=hello.txt")
+            .unwrap();
+        let encoded = codec.encode(b"Hello, world!\n").unwrap();
+        assert_eq!(
+            encoded,
+            b"begin 600 hello.txt\n.2&5L;&\\L('=O<FQD(0H`\n`\nend\n"
+        );
+        assert!(codec.detect(&encoded[..]));
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), b"Hello, world!\n");
+
+        // Old encoders used spaces for zeroes, and some transports strip them
+        let codec = UuencodeCodec::new(Variant::Uu);
+        assert_eq!(codec.decode(b"#0V%T\n \nend\n").unwrap(), b"Cat");
+        assert_eq!(codec.decode(b"\"0   \n").unwrap(), b"@\0");
+        assert_eq!(codec.decode(b"\"0\n").unwrap(), b"@\0");
+
+        // Without a header, lines must be well formed
+        assert!(codec.decode(b"Hello world").is_err());
+        assert!(codec.decode(b"\x00\x01").is_err());
+        assert!(!codec.detect(b"\x00\x1f\n"));
+        assert!(!codec.detect(b"`abc\n"));
+        assert!(!codec.detect(b"   \n"));
+
+        let data: Vec<u8> = (0..=255).collect();
+        let encoded = codec.encode(&data[..]).unwrap();
+        assert!(codec.detect(&encoded[..]));
+        assert!(!UuencodeCodec::new(Variant::Xx).detect(&encoded[..]));
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), data);
+    }
+
+    #[test]
+    fn xxencode() {
+        let codec = UuencodeCodec::new(Variant::Xx);
+        let encoded = codec.encode(b"Cat").unwrap();
+        assert_eq!(encoded, b"begin 644 data\n1Eq3o\n+\nend\n");
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), b"Cat");
+        assert!(codec.decode(b"1Eq3\n").is_err());
+
+        let data: Vec<u8> = (0..=255).rev().collect();
+        let encoded = codec.encode(&data[..]).unwrap();
+        assert!(codec.detect(&encoded[..]));
+        assert_eq!(codec.decode(&encoded[..]).unwrap(), data);
+        assert!(!codec.detect(b"Hello world"));
+    }
+}
@@ -0,0 +1,282 @@
+use crate::codecs::{
+    hex::HexCodec,
+    score::english_score,
+    stream::{transform_all, BufferedReader, Transform, TransformReader},
+    Codec, CodecError, Result, Stream,
+};
+
+/// Number of candidates shown when brute forcing
+const BRUTE_CANDIDATES: usize = 5;
+
+/// Number of bytes shown for each candidate
+const PREVIEW_LEN: usize = 48;
+
+/// Longest repeating key looked for by default
+const DEFAULT_MAX_KEY_LEN: usize = 40;
+
+/// Number of key sizes, from the smallest Hamming distance, that are tried
+const KEY_SIZE_CANDIDATES: usize = 5;
+
+/// Candidate keys scoring this close to the best score are considered as good
+const SCORE_TOLERANCE: f64 = 0.05;
+
+/// Number of blocks compared to compute the Hamming distance of a key size
+const MAX_BLOCKS: usize = 32;
+
+#[derive(Clone)]
+enum Mode {
+    /// Repeating key
+    Key(Vec<u8>),
+
+    /// Tries all single-byte keys
+    Brute,
+
+    /// Guesses a repeating key up to the given length
+    Recover(usize),
+}
+
+/// Sorts all single-byte keys from the most to the least plausible
+fn rank_keys(data: &[u8]) -> Vec<(u8, f64)> {
+    let mut buffer = Vec::with_capacity(data.len());
+    let mut ranking: Vec<_> = (0..=u8::MAX)
+        .map(|key| {
+            buffer.clear();
+            buffer.extend(data.iter().map(|b| b ^ key));
+            (key, english_score(&buffer[..]))
+        })
+        .collect();
+    ranking.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranking
+}
+
+fn hamming_distance(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(a, b)| (a ^ b).count_ones()).sum()
+}
+
+/// Average Hamming distance between consecutive blocks, per byte
+fn normalized_distance(data: &[u8], size: usize) -> f64 {
+    let blocks: Vec<_> = data.chunks_exact(size).take(MAX_BLOCKS).collect();
+    let pairs = blocks.len().saturating_sub(1).max(1);
+    let total: u32 = blocks
+        .windows(2)
+        .map(|w| hamming_distance(w[0], w[1]))
+        .sum();
+    f64::from(total) / (pairs * size) as f64
+}
+
+/// Shortest key producing the same keystream, sizes multiple of the actual one also match
+fn shortest_period(key: &[u8]) -> &[u8] {
+    let period = (1..key.len())
+        .filter(|p| key.len().is_multiple_of(*p))
+        .find(|p| key.iter().enumerate().all(|(i, b)| *b == key[i % p]))
+        .unwrap_or(key.len());
+    &key[..period]
+}
+
+fn recover_key(data: &[u8], max_len: usize) -> Vec<u8> {
+    let mut sizes: Vec<_> = (1..=max_len.min(data.len() / 2).max(1))
+        .map(|size| (size, normalized_distance(data, size)))
+        .collect();
+    sizes.sort_by(|a, b| a.1.total_cmp(&b.1));
+    sizes.truncate(KEY_SIZE_CANDIDATES);
+
+    let mut buffer = Vec::with_capacity(data.len());
+    let candidates: Vec<_> = sizes
+        .into_iter()
+        .map(|(size, _)| {
+            // Each column is xored with a single byte
+            let key: Vec<u8> = (0..size)
+                .map(|column| {
+                    let column: Vec<u8> = data.iter().skip(column).step_by(size).copied().collect();
+                    rank_keys(&column[..])[0].0
+                })
+                .collect();
+            let key = shortest_period(&key[..]).to_vec();
+
+            buffer.clear();
+            transform_all(XorTransform::new(&key[..]), data, &mut buffer).unwrap();
+            (key, english_score(&buffer[..]))
+        })
+        .collect();
+
+    // Longer keys always score a bit better as each byte is guessed on fewer samples, so keep the
+    // shortest one among the best
+    let best_score = candidates
+        .iter()
+        .map(|(_, score)| *score)
+        .fold(f64::MIN, f64::max);
+    candidates
+        .into_iter()
+        .filter(|(_, score)| *score >= best_score - SCORE_TOLERANCE)
+        .min_by_key(|(key, _)| key.len())
+        .map(|(key, _)| key)
+        .unwrap_or_default()
+}
+
+struct XorTransform<'a> {
+    key: &'a [u8],
+    position: usize,
+}
+
+impl<'a> XorTransform<'a> {
+    fn new(key: &'a [u8]) -> Self {
+        Self { key, position: 0 }
+    }
+}
+
+impl Transform for XorTransform<'_> {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>, _eof: bool) -> Result<usize> {
+        for b in input {
+            output.push(b ^ self.key[self.position]);
+            self.position = (self.position + 1) % self.key.len();
+        }
+        Ok(input.len())
+    }
+}
+
+fn parse_byte(arg: &str) -> Option<u8> {
+    match arg.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16).ok(),
+        None => arg.parse().ok(),
+    }
+}
+
+/// XOR with a repeating key, encoding and decoding are the same operation
+#[derive(Clone)]
+pub struct XorCodec {
+    mode: Mode,
+}
+
+impl Default for XorCodec {
+    fn default() -> Self {
+        Self { mode: Mode::Brute }
+    }
+}
+
+impl Codec for XorCodec {
+    fn name(&self) -> &'static str {
+        "xor"
+    }
+
+    fn description(&self) -> &'static str {
+        "XOR with a key, args: BYTE, hex=KEY, key=ASCII, brute (default) or recover[=MAXLEN]"
+    }
+
+    fn encode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        match self.mode {
+            Mode::Key(ref key) => transform_all(XorTransform::new(&key[..]), data, output),
+            Mode::Brute | Mode::Recover(_) => Err(CodecError::MissingKey),
+        }
+    }
+
+    fn decode_into(&self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let key = match self.mode {
+            Mode::Key(ref key) => key.clone(),
+            Mode::Brute => {
+                let ranking = rank_keys(data);
+                for (key, score) in ranking.iter().take(BRUTE_CANDIDATES) {
+                    let preview: Vec<u8> = data.iter().take(PREVIEW_LEN).map(|b| b ^ key).collect();
+                    eprintln!(
+                        "Key 0x{key:02x}: score {score:.2} \"{}\"",
+                        preview.escape_ascii()
+                    );
+                }
+                vec![ranking[0].0]
+            }
+            Mode::Recover(max_len) => {
+                let key = recover_key(data, max_len);
+                eprintln!(
+                    "Recovered {} bytes key: {} \"{}\"",
+                    key.len(),
+                    String::from_utf8(HexCodec::default().encode(&key[..])?).unwrap(),
+                    key.escape_ascii()
+                );
+                key
+            }
+        };
+        transform_all(XorTransform::new(&key[..]), data, output)
+    }
+
+    fn build(&self, args: &str) -> Option<super::Plugin> {
+        let mode = match args.split_once('=') {
+            None => match args {
+                "" | "brute" => Mode::Brute,
+                "recover" => Mode::Recover(DEFAULT_MAX_KEY_LEN),
+                byte => Mode::Key(vec![parse_byte(byte)?]),
+            },
+            Some(("hex", key)) => Mode::Key(HexCodec::default().decode(key.as_bytes()).ok()?),
+            Some(("key", key)) => Mode::Key(key.as_bytes().to_vec()),
+            Some(("recover", max_len)) => Mode::Recover(max_len.parse().ok().filter(|l| *l > 0)?),
+            _ => return None,
+        };
+        if matches!(mode, Mode::Key(ref key) if key.is_empty()) {
+            return None;
+        }
+        Some(Box::new(Self { mode }) as super::Plugin)
+    }
+
+    fn detect(&self, data: &[u8]) -> bool {
+        // Anything can be xored
+        let _ = data;
+        false
+    }
+
+    fn encoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        match self.mode {
+            Mode::Key(ref key) => Box::new(TransformReader::new(reader, XorTransform::new(key))),
+            _ => Box::new(BufferedReader::new(reader, move |data| self.encode(data))),
+        }
+    }
+
+    fn decoder<'a>(&'a self, reader: Stream<'a>) -> Stream<'a> {
+        match self.mode {
+            Mode::Key(ref key) => Box::new(TransformReader::new(reader, XorTransform::new(key))),
+            // Needs the whole input to guess the key
+            _ => Box::new(BufferedReader::new(reader, move |data| self.decode(data))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &[u8] = b"Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal. \
+        It was the best of times, it was the worst of times, it was the age of wisdom, it was the \
+        age of foolishness, it was the epoch of belief, it was the epoch of incredulity, it was the \
+        season of Light, it was the season of Darkness, it was the spring of hope, it was the \
+        winter of despair, we had everything before us, we had nothing before us.";
+
+    fn codec(mode: Mode) -> XorCodec {
+        XorCodec { mode }
+    }
+
+    #[test]
+    fn repeating_key() {
+        let ice = codec(Mode::Key(b"ICE".to_vec()));
+        let encoded = ice.encode(&TEXT[..74]).unwrap();
+        assert_eq!(
+            HexCodec::default().encode(&encoded[..]).unwrap(),
+            &b"0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f"[..]
+        );
+        assert_eq!(ice.decode(&encoded[..]).unwrap(), &TEXT[..74]);
+    }
+
+    #[test]
+    fn brute_force() {
+        let encoded = codec(Mode::Key(vec![0x5a])).encode(TEXT).unwrap();
+        assert_eq!(codec(Mode::Brute).decode(&encoded[..]).unwrap(), TEXT);
+    }
+
+    #[test]
+    fn recover() {
+        let encoded = codec(Mode::Key(b"Terminator X".to_vec()))
+            .encode(TEXT)
+            .unwrap();
+        assert_eq!(
+            recover_key(&encoded[..], DEFAULT_MAX_KEY_LEN),
+            b"Terminator X"
+        );
+        assert_eq!(shortest_period(b"abcabc"), b"abc");
+    }
+}
@@ -1,4 +1,7 @@
-use std::io::{self, Read, Write};
+use std::{
+    fs::File,
+    io::{self, BufReader},
+};
 
 use clap::{Parser, ValueEnum};
 
@@ -29,7 +32,7 @@ struct Options {
     input: Option<String>,
 }
 
-use codecs::{get_available_plugins, Codec, Plugin};
+use codecs::{get_available_plugins, Codec, Plugin, Stream};
 
 fn get_codec_by_prefix(prefix: &str) -> Plugin {
     let (prefix, args) = match prefix.split_once(':') {
@@ -79,11 +82,11 @@ fn get_codec_by_prefix(prefix: &str) -> Plugin {
     }
 }
 
-fn read_stdin() -> io::Result<Vec<u8>> {
-    let mut stdin = io::stdin().lock();
-    let mut buffer = Vec::new();
-    stdin.read_to_end(&mut buffer)?;
-    Ok(buffer)
+fn open_input(input: Option<&str>) -> io::Result<Stream<'static>> {
+    match input {
+        None | Some("-") => Ok(Box::new(io::stdin().lock())),
+        Some(p) => Ok(Box::new(BufReader::new(File::open(p)?))),
+    }
 }
 
 fn main() -> codecs::Result<()> {
@@ -104,38 +107,27 @@ fn main() -> codecs::Result<()> {
         .collect();
 
     if transformations.is_empty() {
-        let auto_recurse_codec = codecs::auto::AutoRecurseCodec;
+        let auto_recurse_codec = codecs::auto::AutoRecurseCodec::default();
         transformations.push(auto_recurse_codec.build("").unwrap());
     }
 
-    let mut input = match args.input {
-        None => read_stdin()?,
-        Some(ref p) => {
-            if p == "-" {
-                read_stdin()?
-            } else {
-                std::fs::read(p)?
-            }
-        }
-    };
-
+    // Each codec wraps the previous stream, so data flows through the whole chain in chunks
+    let mut stream = open_input(args.input.as_deref())?;
     for t in &transformations[..] {
-        let output = match args.mode {
+        stream = match args.mode {
             Mode::Encode => {
                 eprintln!("Encoding with {} ({})", t.name(), t.description());
-                t.encode(&input[..])?
+                t.encoder(stream)
             }
             Mode::Decode => {
                 eprintln!("Decoding with {} ({})", t.name(), t.description());
-                t.decode(&input[..])?
+                t.decoder(stream)
             }
         };
-
-        input = output;
     }
 
     let mut stdout = io::stdout().lock();
-    stdout.write_all(&input[..])?;
+    io::copy(&mut stream, &mut stdout)?;
 
     Ok(())
 }